@@ -0,0 +1,469 @@
+use boggle::io::Error;
+use boggle::Pattern;
+use boggle::Scoring;
+use clap::builder::{PossibleValuesParser, RangedU64ValueParser, TypedValueParser};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use std::env;
+use std::fs;
+use std::iter;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Play Boggle in the terminal.
+///
+/// Defaults for any of the options can be set in `$XDG_CONFIG_HOME/boggle/config`, one
+/// `option = value` per line, for example `timer = 120`. Dictionaries and dice sets are looked
+/// for in `$XDG_DATA_HOME/boggle`, then in the current directory.
+#[derive(Debug, Parser)]
+#[command(name = "boggle-cli", version)]
+pub struct Cli {
+    #[command(flatten)]
+    pub game: GameArgs,
+    #[command(flatten)]
+    pub play: PlayArgs,
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Print every word on a board
+    Solve(SolveArgs),
+    /// Roll many boards and summarise the words on them, to compare dice sets and dictionaries
+    Stats(StatsArgs),
+    /// Play against others on a boggle-server
+    Join(JoinArgs),
+    /// Practise the words you keep missing, on boards made to contain them
+    Practice(PracticeArgs),
+    /// Look back on the rounds played
+    History {
+        #[command(subcommand)]
+        command: Option<HistoryCommand>,
+    },
+    /// Manage word lists
+    Dict {
+        #[command(subcommand)]
+        command: DictCommand,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum HistoryCommand {
+    /// List the rounds played, oldest first (the default)
+    List {
+        /// Only list the most recent rounds
+        #[arg(short = 'n', long, value_name = "ROUNDS")]
+        limit: Option<usize>,
+    },
+    /// Show the best rounds played under --name
+    Best,
+    /// Show how the player called --name is getting on
+    Stats {
+        /// Number of missed words to list, those that cost the most points first
+        #[arg(long, value_name = "WORDS", default_value_t = 10)]
+        missed: usize,
+    },
+    /// Show the board and results of a round again
+    Show {
+        /// Number of the round, as listed by `history list`
+        round: usize,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum DictCommand {
+    /// Add words to the custom dictionary
+    Add {
+        #[arg(required = true)]
+        words: Vec<String>,
+    },
+    /// Remove words from the custom dictionary
+    Remove {
+        #[arg(required = true)]
+        words: Vec<String>,
+    },
+    /// Check whether words are in the dictionary
+    Check {
+        #[arg(required = true)]
+        words: Vec<String>,
+    },
+    /// Add the words from other word lists to the custom dictionary, or combine them into one
+    /// file with --output
+    Merge {
+        #[arg(required = true, value_name = "PATH")]
+        inputs: Vec<PathBuf>,
+        #[arg(short, long, value_name = "PATH")]
+        output: Option<PathBuf>,
+    },
+    /// Count the words in the dictionary by length
+    Stats,
+    /// Convert a word list between text, with one word per line, and JSON, chosen by the file
+    /// extension
+    Convert {
+        input: PathBuf,
+        output: PathBuf,
+        /// Write JSON as nested objects, one per letter, which is smaller for large lists
+        #[arg(long)]
+        trie: bool,
+    },
+}
+
+/// Options for the board and the dictionary.
+#[derive(Debug, Default, Args)]
+pub struct GameArgs {
+    /// Word list to play with, one word per line. Give more than once to combine several
+    /// [default: the custom dictionary]
+    #[arg(short, long = "dict", global = true, value_name = "PATH")]
+    pub dicts: Vec<PathBuf>,
+    /// Words added to the --dict word lists, by `dict add` or by accepting them in a game
+    /// [default: dictionaries/custom.txt]
+    #[arg(long, global = true, value_name = "PATH")]
+    pub custom: Option<PathBuf>,
+    /// Words removed from the --dict word lists by `dict remove`
+    /// [default: dictionaries/removed.txt]
+    #[arg(long, global = true, value_name = "PATH")]
+    pub removed: Option<PathBuf>,
+    /// Words that score, but are never shown as hints or missed words
+    /// [default: dictionaries/blocklist.txt]
+    #[arg(long, global = true, value_name = "PATH")]
+    pub blocklist: Option<PathBuf>,
+    /// Definitions to show with the results, as JSON or tab-separated text
+    /// [default: dictionaries/definitions.json]
+    #[arg(long, global = true, value_name = "PATH")]
+    pub definitions: Option<PathBuf>,
+    /// Dice set to roll, either a file or the name of a set in the dice data directory
+    /// [default: classic]
+    #[arg(long, global = true, value_name = "NAME|PATH")]
+    pub dice: Option<String>,
+    /// Seed for rolling the boards, to play the same boards again
+    #[arg(long, global = true)]
+    pub seed: Option<u64>,
+    /// How words score [default: classic]
+    #[arg(
+        long,
+        global = true,
+        value_name = "PRESET",
+        value_parser = PossibleValuesParser::new(Scoring::ALL.map(Scoring::name))
+            .map(|s| s.parse::<Scoring>().unwrap()),
+    )]
+    pub scoring: Option<Scoring>,
+    /// Shortest word that scores, counting Qu as two letters. Word lists never hold words shorter
+    /// than 3 letters, so it can't be less [default: 3]
+    #[arg(
+        long,
+        global = true,
+        value_name = "LETTERS",
+        value_parser = RangedU64ValueParser::<usize>::new().range(3..),
+    )]
+    pub min_len: Option<usize>,
+    /// How results are printed [default: text]
+    #[arg(long, global = true, value_enum)]
+    pub format: Option<Format>,
+    /// Print results as JSON, the same as --format json
+    #[arg(long, global = true, conflicts_with = "format")]
+    pub json: bool,
+    /// Name to save rounds under, and to play under on a server [default: your user name]
+    #[arg(long, global = true)]
+    pub name: Option<String>,
+    /// Where to save the rounds played [default: history.jsonl]
+    #[arg(long, global = true, value_name = "PATH")]
+    pub history: Option<PathBuf>,
+    /// Don't save the rounds played
+    #[arg(long, global = true, conflicts_with = "history")]
+    pub no_history: bool,
+}
+
+/// Options for playing rounds.
+#[derive(Debug, Default, Args)]
+pub struct PlayArgs {
+    /// Length of a round in seconds, or 0 to play without a timer [default: 180]
+    #[arg(long, value_name = "SECS")]
+    pub timer: Option<u64>,
+    /// Check each word as soon as it is entered
+    #[arg(long, overrides_with = "no_feedback")]
+    pub feedback: bool,
+    /// Only check the words once the round is over, the default unless the config file says
+    /// otherwise
+    #[arg(long, overrides_with = "feedback")]
+    pub no_feedback: bool,
+    /// Play full screen, with the board highlighted as you type
+    #[cfg(feature = "tui")]
+    #[arg(long)]
+    pub tui: bool,
+}
+
+/// Options for the `solve` subcommand.
+#[derive(Debug, Args)]
+pub struct SolveArgs {
+    /// The board, as four rows of four letters or one tile per word [default: read from --file or
+    /// standard input]
+    #[arg(value_name = "BOARD")]
+    pub board: Vec<String>,
+    /// Read the board from a file, in the layout the game prints it in
+    #[arg(short, long, value_name = "PATH", conflicts_with = "board")]
+    pub file: Option<PathBuf>,
+    /// Print the tiles spelling out each word, as row,column pairs counting from 0
+    #[arg(long)]
+    pub paths: bool,
+    /// Order to print the words in
+    #[arg(long, value_enum, default_value_t = SortOrder::Score)]
+    pub sort: SortOrder,
+    /// Print the words in the opposite order
+    #[arg(long)]
+    pub reverse: bool,
+    /// Only print words matching a pattern, with ? for any letter and * for any run of letters
+    #[arg(long)]
+    pub pattern: Option<Pattern>,
+    /// Only print words scoring at least this many points
+    #[arg(long, value_name = "POINTS")]
+    pub min_score: Option<u32>,
+    /// Only print words with at most this many letters, counting Qu as two
+    #[arg(long, value_name = "LETTERS")]
+    pub max_len: Option<usize>,
+    /// Print at most this many words
+    #[arg(short = 'n', long)]
+    pub limit: Option<usize>,
+}
+
+/// Options for the `stats` subcommand.
+#[derive(Debug, Args)]
+pub struct StatsArgs {
+    /// Number of boards to roll
+    #[arg(short = 'n', long, default_value_t = 1000)]
+    pub boards: usize,
+    /// Number of longest and most common words to list
+    #[arg(long, default_value_t = 20)]
+    pub top: usize,
+}
+
+/// Options for the `practice` subcommand.
+#[derive(Debug, Args)]
+pub struct PracticeArgs {
+    /// Start practising words missed in at least this many rounds of the history
+    #[arg(long, value_name = "ROUNDS", default_value_t = 2)]
+    pub min_missed: u32,
+    /// Where to keep track of when each word is next due [default: practice.json]
+    #[arg(long, value_name = "PATH")]
+    pub schedule: Option<PathBuf>,
+}
+
+impl PracticeArgs {
+    /// The practice schedule, which may not exist yet.
+    pub fn schedule(&self) -> Option<PathBuf> {
+        self.schedule
+            .clone()
+            .or_else(|| data_file_or_new("practice.json"))
+    }
+}
+
+/// Options for the `join` subcommand.
+#[derive(Debug, Args)]
+pub struct JoinArgs {
+    /// Server to connect to, as HOST:PORT
+    pub address: String,
+    /// Room to play in; everyone in a room plays the same board
+    #[arg(long, default_value = "lobby")]
+    pub room: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum SortOrder {
+    /// Highest scoring first, then alphabetically
+    Score,
+    /// Longest first, then alphabetically
+    Length,
+    /// Alphabetically
+    Alpha,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    /// Readable text
+    Text,
+    /// Comma-separated values, with a header row
+    Csv,
+    Json,
+}
+
+impl Cli {
+    /// Parses the command line, filling in anything not given from the config file. Options given
+    /// on the command line win over the config file.
+    pub fn load() -> Result<Self, Error> {
+        let mut cli = Cli::parse();
+        if let Some(config) = read_config()? {
+            cli.game.merge(config.game);
+            cli.play.merge(config.play);
+        }
+        Ok(cli)
+    }
+}
+
+impl GameArgs {
+    fn merge(&mut self, config: GameArgs) {
+        if self.dicts.is_empty() {
+            self.dicts = config.dicts;
+        }
+        self.custom = self.custom.take().or(config.custom);
+        self.removed = self.removed.take().or(config.removed);
+        self.blocklist = self.blocklist.take().or(config.blocklist);
+        self.definitions = self.definitions.take().or(config.definitions);
+        self.dice = self.dice.take().or(config.dice);
+        self.seed = self.seed.or(config.seed);
+        self.scoring = self.scoring.or(config.scoring);
+        self.min_len = self.min_len.or(config.min_len);
+        // --format and --json, and --history and --no-history, are each one setting, so the config
+        // only fills them in when neither was given
+        if self.format.is_none() && !self.json {
+            self.format = config.format;
+            self.json = config.json;
+        }
+        self.name = self.name.take().or(config.name);
+        if self.history.is_none() && !self.no_history {
+            self.history = config.history;
+            self.no_history = config.no_history;
+        }
+    }
+
+    /// The custom dictionary, which may not exist yet.
+    pub fn custom(&self) -> Option<PathBuf> {
+        self.custom
+            .clone()
+            .or_else(|| data_file_or_new("dictionaries/custom.txt"))
+    }
+
+    /// The words removed from the dictionary, which may not exist yet.
+    pub fn removed(&self) -> Option<PathBuf> {
+        self.removed
+            .clone()
+            .or_else(|| data_file_or_new("dictionaries/removed.txt"))
+    }
+
+    /// The history log, which may not exist yet, or `None` if rounds aren't saved.
+    pub fn history(&self) -> Option<PathBuf> {
+        if self.no_history {
+            return None;
+        }
+        self.history
+            .clone()
+            .or_else(|| data_file_or_new("history.jsonl"))
+    }
+
+    pub fn name(&self) -> String {
+        self.name
+            .clone()
+            .or_else(|| env::var("USER").ok())
+            .unwrap_or_else(|| String::from("player"))
+    }
+
+    pub fn scoring(&self) -> Scoring {
+        self.scoring.unwrap_or_default()
+    }
+
+    pub fn min_len(&self) -> usize {
+        self.min_len.unwrap_or(3)
+    }
+
+    pub fn format(&self) -> Format {
+        match self.format {
+            Some(format) => format,
+            None if self.json => Format::Json,
+            None => Format::Text,
+        }
+    }
+}
+
+impl PlayArgs {
+    fn merge(&mut self, config: PlayArgs) {
+        self.timer = self.timer.or(config.timer);
+        if !self.feedback && !self.no_feedback {
+            self.feedback = config.feedback;
+        }
+        #[cfg(feature = "tui")]
+        {
+            self.tui |= config.tui;
+        }
+    }
+
+    /// How long each round lasts, or `None` to wait for a blank line.
+    pub fn timer(&self) -> Option<Duration> {
+        match self.timer.unwrap_or(180) {
+            0 => None,
+            secs => Some(Duration::from_secs(secs)),
+        }
+    }
+}
+
+/// Reads the config file, if there is one, by turning each line into the matching option.
+fn read_config() -> Result<Option<Cli>, Error> {
+    let path = match config_dir() {
+        Some(dir) => dir.join("config"),
+        None => return Ok(None),
+    };
+    let text = match fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err.into()),
+    };
+    let mut args = Vec::new();
+    for line in text.lines() {
+        let line = strip_comment(line);
+        if line.is_empty() {
+            continue;
+        }
+        match line.split_once('=') {
+            Some((key, value)) => match value.trim() {
+                "true" => args.push(format!("--{}", key.trim())),
+                "false" => {}
+                value => {
+                    args.push(format!("--{}", key.trim()));
+                    args.push(value.to_string());
+                }
+            },
+            None => args.push(format!("--{}", line)),
+        }
+    }
+    Cli::try_parse_from(iter::once("boggle-cli".to_string()).chain(args))
+        .map(Some)
+        .map_err(|err| Error::StringMessage(format!("{}: {}", path.display(), err)))
+}
+
+/// Cuts a config line off where a comment starts: at a `#` starting the line or following
+/// whitespace, so that paths containing `#` are kept whole.
+fn strip_comment(line: &str) -> &str {
+    let line = line.trim();
+    let start = line
+        .char_indices()
+        .find(|&(idx, c)| c == '#' && line[..idx].chars().last().is_none_or(char::is_whitespace))
+        .map_or(line.len(), |(idx, _)| idx);
+    line[..start].trim_end()
+}
+
+fn xdg_dir(var: &str, fallback: &str) -> Option<PathBuf> {
+    match env::var_os(var) {
+        Some(dir) if Path::new(&dir).is_absolute() => Some(PathBuf::from(dir)),
+        _ => env::var_os("HOME").map(|home| Path::new(&home).join(fallback)),
+    }
+    .map(|dir| dir.join("boggle"))
+}
+
+fn config_dir() -> Option<PathBuf> {
+    xdg_dir("XDG_CONFIG_HOME", ".config")
+}
+
+pub fn data_dir() -> Option<PathBuf> {
+    xdg_dir("XDG_DATA_HOME", ".local/share")
+}
+
+/// Finds a data file, or where to create it in the data directory if there isn't one.
+fn data_file_or_new(name: &str) -> Option<PathBuf> {
+    find_data_file(name).or_else(|| data_dir().map(|dir| dir.join(name)))
+}
+
+/// Finds a data file, looking in the data directory first and then in the current directory.
+pub fn find_data_file(name: &str) -> Option<PathBuf> {
+    data_dir()
+        .map(|dir| dir.join(name))
+        .into_iter()
+        .chain(iter::once(PathBuf::from(name)))
+        .find(|path| path.is_file())
+}
@@ -0,0 +1,191 @@
+use crate::args::{DictCommand, Format, GameArgs};
+use crate::play::csv_field;
+use crate::{load_base, load_blocklist, load_dicts, Overlay};
+use boggle::io::*;
+use boggle::*;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+pub fn dict(args: &GameArgs, command: &DictCommand) -> Result<(), Error> {
+    match command {
+        DictCommand::Add { words } => {
+            let words = parse_words(words, args.min_len())?;
+            let (base, overlay) = load_overlay(args)?;
+            let mut dict = overlay.read(&base)?;
+            for word in words.iter() {
+                if dict.insert(word) {
+                    println!("Added {}", word);
+                } else {
+                    println!("{} was already in the dictionary", word);
+                }
+            }
+            overlay.write(&dict)
+        }
+        DictCommand::Remove { words } => {
+            // words already in the dictionary can be removed whatever their length
+            let words = parse_words(words, 0)?;
+            let (base, overlay) = load_overlay(args)?;
+            let mut dict = overlay.read(&base)?;
+            for word in words.iter() {
+                if dict.remove(word) {
+                    println!("Removed {}", word);
+                } else {
+                    println!("{} was not in the dictionary", word);
+                }
+            }
+            overlay.write(&dict)
+        }
+        DictCommand::Check { words } => check(args, words),
+        DictCommand::Merge {
+            inputs,
+            output: Some(output),
+        } => {
+            let mut merged = Dict::new();
+            for input in inputs.iter() {
+                merged |= &read_words(input)?;
+            }
+            write_dict(output, &merged)?;
+            println!("Wrote {} words to {}", merged.len(), output.display());
+            Ok(())
+        }
+        DictCommand::Merge {
+            inputs,
+            output: None,
+        } => {
+            let (base, overlay) = load_overlay(args)?;
+            let mut dict = overlay.read(&base)?;
+            let before = dict.len();
+            for input in inputs.iter() {
+                for word in read_words(input)?.iter() {
+                    dict.insert(&word);
+                }
+            }
+            println!("Added {} words", dict.len() - before);
+            overlay.write(&dict)
+        }
+        DictCommand::Stats => stats(args),
+        DictCommand::Convert {
+            input,
+            output,
+            trie,
+        } => {
+            let dict = read_words(input)?;
+            if is_json(output) {
+                let json = if *trie {
+                    boggle::serde::trie::serialize(&dict, serde_json::value::Serializer)
+                } else {
+                    serde_json::to_value(&dict)
+                }
+                .map_err(|err| Error::StringMessage(err.to_string()))?;
+                fs::write(output, json.to_string())?;
+                Ok(())
+            } else {
+                write_dict(output, &dict)
+            }
+        }
+    }
+}
+
+fn load_overlay(args: &GameArgs) -> Result<(Dict, Overlay), Error> {
+    let overlay = Overlay::from_args(args).ok_or(Error::SimpleMessage(
+        "no data directory for the custom dictionary; pass --custom and --removed",
+    ))?;
+    Ok((load_base(args)?, overlay))
+}
+
+fn parse_word(word: &str, min_len: usize) -> Result<BString, Error> {
+    if word.len() < min_len {
+        return Err(Error::StringMessage(format!("word too short: {}", word)));
+    }
+    word.parse()
+        .map_err(|_| Error::StringMessage(format!("invalid word: {}", word)))
+}
+
+/// Parses all the words up front, so that nothing is saved if any of them are invalid.
+fn parse_words(words: &[String], min_len: usize) -> Result<Vec<BString>, Error> {
+    words.iter().map(|word| parse_word(word, min_len)).collect()
+}
+
+fn is_json(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "json")
+}
+
+/// Reads a word list as text, or as JSON in either of the layouts `convert` writes.
+fn read_words(path: &Path) -> Result<Dict, Error> {
+    if !is_json(path) {
+        return read_dict(path);
+    }
+    let text = fs::read_to_string(path)?;
+    let dict = if text.trim_start().starts_with('{') {
+        boggle::serde::trie::deserialize(&mut serde_json::Deserializer::from_str(&text))
+    } else {
+        serde_json::from_str(&text)
+    };
+    dict.map_err(|err| Error::StringMessage(format!("{}: {}", path.display(), err)))
+}
+
+fn check(args: &GameArgs, words: &[String]) -> Result<(), Error> {
+    let dict = load_dicts(args)?;
+    let block = load_blocklist(args)?;
+    let statuses: Vec<(&str, &str)> = words
+        .iter()
+        .map(|word| {
+            let status = match word.parse::<BString>() {
+                Ok(bword) if dict.contains(&bword) && block.contains(&bword) => "blocked",
+                Ok(bword) if dict.contains(&bword) => "found",
+                Ok(_) => "missing",
+                Err(_) => "invalid",
+            };
+            (word.as_str(), status)
+        })
+        .collect();
+    match args.format() {
+        Format::Text => {
+            for (word, status) in statuses {
+                match status {
+                    "blocked" => println!("{} is in the dictionary, but blocked", word),
+                    "found" => println!("{} is in the dictionary", word),
+                    "missing" => println!("{} is not in the dictionary", word),
+                    _ => println!("{} is not possible in Boggle", word),
+                }
+            }
+        }
+        Format::Csv => {
+            println!("word,status");
+            for (word, status) in statuses {
+                println!("{},{}", csv_field(word), status);
+            }
+        }
+        Format::Json => {
+            let statuses: BTreeMap<_, _> = statuses.into_iter().collect();
+            println!("{}", serde_json::json!(statuses));
+        }
+    }
+    Ok(())
+}
+
+fn stats(args: &GameArgs) -> Result<(), Error> {
+    let dict = load_dicts(args)?;
+    let mut by_len = BTreeMap::new();
+    dict.traverse(|word| *by_len.entry(word.to_string().len()).or_insert(0) += 1);
+    match args.format() {
+        Format::Text => {
+            println!("{} words", dict.len());
+            for (len, count) in by_len.iter() {
+                println!("{:>3} letters: {}", len, count);
+            }
+        }
+        Format::Csv => {
+            println!("letters,words");
+            for (len, count) in by_len.iter() {
+                println!("{},{}", len, count);
+            }
+        }
+        Format::Json => println!(
+            "{}",
+            serde_json::json!({ "words": dict.len(), "by_length": by_len })
+        ),
+    }
+    Ok(())
+}
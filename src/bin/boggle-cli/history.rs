@@ -0,0 +1,422 @@
+use crate::args::{Format, GameArgs, HistoryCommand};
+use crate::join::print_results;
+use crate::play::csv_field;
+use crate::{load_definitions, Game};
+use boggle::history::*;
+use boggle::io::*;
+use boggle::round::RoundResult;
+use boggle::*;
+use std::collections::BTreeMap;
+use std::fs;
+use std::mem;
+use std::path::Path;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+/// Keeps track of a round as it is played, to be saved to the history log once it is over.
+pub struct Recorder {
+    time: u64,
+    started: Instant,
+    board: Board,
+    words: Vec<WordRecord>,
+}
+
+/// The time now, in seconds since the Unix epoch.
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.as_secs())
+}
+
+impl Recorder {
+    pub fn new(board: Board) -> Self {
+        Recorder {
+            time: now(),
+            started: Instant::now(),
+            board,
+            words: Vec::new(),
+        }
+    }
+
+    /// Notes a word the player entered.
+    pub fn word(&mut self, word: &str) {
+        self.words.push(WordRecord {
+            word: word.to_string(),
+            millis: self.started.elapsed().as_millis() as u64,
+        });
+    }
+
+    /// Makes the record of the round, with the words entered given to the player called `name`.
+    pub fn finish<'r>(
+        mut self,
+        name: &str,
+        results: impl IntoIterator<Item = (&'r str, &'r RoundResult)>,
+        scoring: Scoring,
+        min_len: usize,
+    ) -> GameRecord {
+        let players = results
+            .into_iter()
+            .map(|(player, result)| PlayerRecord {
+                name: player.to_string(),
+                words: if player == name {
+                    mem::take(&mut self.words)
+                } else {
+                    Vec::new()
+                },
+                result: result.clone(),
+                score: result.score_with(scoring),
+            })
+            .collect();
+        GameRecord {
+            time: self.time,
+            board: self.board,
+            seed: None,
+            dice: None,
+            dict: None,
+            scoring,
+            min_len,
+            players,
+        }
+    }
+
+    /// Saves a round played alone against `dict`, if the history log is kept.
+    pub fn save(self, game: &Game, dict: &Dict, result: &RoundResult) -> Result<(), Error> {
+        let mut record = self.finish(
+            &game.name,
+            [(game.name.as_str(), result)],
+            game.scoring,
+            game.min_len,
+        );
+        record.seed = game.seed;
+        record.dice = game.dice.name.clone();
+        record.dict = Some(dict_id(dict));
+        save(game.history.as_deref(), &record)
+    }
+}
+
+/// Adds a round to the history log, creating its directory if needed.
+pub fn save(path: Option<&Path>, record: &GameRecord) -> Result<(), Error> {
+    let path = match path {
+        Some(path) => path,
+        None => return Ok(()),
+    };
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    append_history(path, record)
+}
+
+pub fn history(args: &GameArgs, command: &Option<HistoryCommand>) -> Result<(), Error> {
+    let path = args.history().ok_or(Error::SimpleMessage(
+        "no history log; pass one with --history",
+    ))?;
+    let games = read_history(path)?;
+    match command {
+        None => list(args, &games, None),
+        Some(HistoryCommand::List { limit }) => list(args, &games, *limit),
+        Some(HistoryCommand::Best) => best(args, &games),
+        Some(HistoryCommand::Stats { missed }) => stats(args, &games, *missed),
+        Some(HistoryCommand::Show { round }) => show(args, &games, *round),
+    }
+}
+
+/// Formats a time in seconds since the Unix epoch as a date and time in UTC.
+pub fn format_date(time: u64) -> String {
+    let days = (time / 86_400) as i64;
+    let secs = time % 86_400;
+    // Howard Hinnant's days_from_civil, run backwards
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + (month <= 2) as i64;
+    format!(
+        "{}-{:02}-{:02} {:02}:{:02}",
+        year,
+        month,
+        day,
+        secs / 3600,
+        secs % 3600 / 60
+    )
+}
+
+/// The board as four rows of letters, as `solve` accepts it.
+fn format_board(board: &Board) -> String {
+    (0..4)
+        .map(|r| (0..4).map(|c| board[r][c].to_string()).collect::<String>())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn list(args: &GameArgs, games: &[GameRecord], limit: Option<usize>) -> Result<(), Error> {
+    let skip = limit.map_or(0, |limit| games.len().saturating_sub(limit));
+    let rounds = games.iter().enumerate().skip(skip).map(|(i, g)| (i + 1, g));
+    match args.format() {
+        Format::Text => {
+            if games.is_empty() {
+                println!("No rounds played yet");
+                return Ok(());
+            }
+            println!(
+                "{:>5}  {:<16}  {:<19}  Scores",
+                "Round", "Date (UTC)", "Board"
+            );
+            for (round, game) in rounds {
+                let scores: Vec<String> = game
+                    .players
+                    .iter()
+                    .map(|player| format!("{} {}", player.name, player.score))
+                    .collect();
+                println!(
+                    "{:>5}  {:<16}  {:<19}  {}",
+                    round,
+                    format_date(game.time),
+                    format_board(&game.board),
+                    scores.join(", ")
+                );
+            }
+        }
+        Format::Csv => {
+            println!("round,time,board,dice,player,score");
+            for (round, game) in rounds {
+                for player in game.players.iter() {
+                    println!(
+                        "{},{},{},{},{},{}",
+                        round,
+                        game.time,
+                        format_board(&game.board),
+                        csv_field(game.dice.as_deref().unwrap_or("")),
+                        csv_field(&player.name),
+                        player.score
+                    );
+                }
+            }
+        }
+        Format::Json => {
+            let rounds: Vec<_> = rounds
+                .map(|(round, game)| {
+                    let scores: BTreeMap<&str, u32> = game
+                        .players
+                        .iter()
+                        .map(|player| (player.name.as_str(), player.score))
+                        .collect();
+                    serde_json::json!({
+                        "round": round,
+                        "time": game.time,
+                        "board": game.board,
+                        "dice": game.dice,
+                        "scores": scores,
+                    })
+                })
+                .collect();
+            println!("{}", serde_json::json!(rounds));
+        }
+    }
+    Ok(())
+}
+
+/// A personal best, and the round it was set in.
+struct Best<T> {
+    value: T,
+    round: usize,
+    time: u64,
+}
+
+fn best(args: &GameArgs, games: &[GameRecord]) -> Result<(), Error> {
+    let name = args.name();
+    let mut played = 0;
+    let mut score: Option<Best<u32>> = None;
+    let mut words: Option<Best<usize>> = None;
+    let mut longest: Option<Best<&str>> = None;
+    for (i, game) in games.iter().enumerate() {
+        let player = match game.player(&name) {
+            Some(player) => player,
+            None => continue,
+        };
+        played += 1;
+        if score.as_ref().is_none_or(|b| player.score > b.value) {
+            score = Some(Best {
+                value: player.score,
+                round: i + 1,
+                time: game.time,
+            });
+        }
+        let found = player.result.correct.len();
+        if words.as_ref().is_none_or(|b| found > b.value) {
+            words = Some(Best {
+                value: found,
+                round: i + 1,
+                time: game.time,
+            });
+        }
+        if let Some(word) = player.longest_word() {
+            if longest.as_ref().is_none_or(|b| word.len() > b.value.len()) {
+                longest = Some(Best {
+                    value: word,
+                    round: i + 1,
+                    time: game.time,
+                });
+            }
+        }
+    }
+    match args.format() {
+        Format::Text => {
+            if played == 0 {
+                println!("No rounds played as {}", name);
+                return Ok(());
+            }
+            println!("{} rounds played as {}", played, name);
+            if let Some(b) = &score {
+                println!(
+                    "Best score: {} (round {}, {})",
+                    b.value,
+                    b.round,
+                    format_date(b.time)
+                );
+            }
+            if let Some(b) = &words {
+                println!(
+                    "Most words: {} (round {}, {})",
+                    b.value,
+                    b.round,
+                    format_date(b.time)
+                );
+            }
+            if let Some(b) = &longest {
+                println!(
+                    "Longest word: {} (round {}, {})",
+                    b.value,
+                    b.round,
+                    format_date(b.time)
+                );
+            }
+        }
+        Format::Csv => {
+            println!("record,value,round");
+            println!("rounds,{},", played);
+            if let Some(b) = &score {
+                println!("score,{},{}", b.value, b.round);
+            }
+            if let Some(b) = &words {
+                println!("words,{},{}", b.value, b.round);
+            }
+            if let Some(b) = &longest {
+                println!("longest_word,{},{}", b.value, b.round);
+            }
+        }
+        Format::Json => {
+            let best = |value: serde_json::Value, round: usize| serde_json::json!({ "value": value, "round": round });
+            println!(
+                "{}",
+                serde_json::json!({
+                    "name": name,
+                    "rounds": played,
+                    "score": score.map(|b| best(b.value.into(), b.round)),
+                    "words": words.map(|b| best(b.value.into(), b.round)),
+                    "longest_word": longest.map(|b| best(b.value.into(), b.round)),
+                })
+            );
+        }
+    }
+    Ok(())
+}
+
+fn stats(args: &GameArgs, games: &[GameRecord], missed: usize) -> Result<(), Error> {
+    let mut stats = PlayerStats::new(games, &args.name());
+    stats.missed.truncate(missed);
+    match args.format() {
+        Format::Text => {
+            if stats.rounds == 0 {
+                println!("No rounds played as {}", stats.name);
+                return Ok(());
+            }
+            println!("{} rounds played as {}", stats.rounds, stats.name);
+            println!("Average score: {:.1}", stats.average_score);
+            println!("Points found: {:.1}%", stats.points_found * 100.0);
+            if let Some(word) = &stats.longest_word {
+                println!("Longest word: {}", word);
+            }
+            println!(
+                "Improvement: {:+.2}% of the points each round",
+                stats.improvement * 100.0
+            );
+            if !stats.missed.is_empty() {
+                println!("Most costly missed words:");
+                for word in stats.missed.iter() {
+                    println!(
+                        "{}: missed {} times, {} points",
+                        word.word, word.times, word.points
+                    );
+                }
+            }
+        }
+        Format::Csv => {
+            println!("section,name,value");
+            println!("player,rounds,{}", stats.rounds);
+            println!("player,average_score,{}", stats.average_score);
+            println!("player,points_found,{}", stats.points_found);
+            println!(
+                "player,longest_word,{}",
+                stats.longest_word.as_deref().unwrap_or("")
+            );
+            println!("player,improvement,{}", stats.improvement);
+            for word in stats.missed.iter() {
+                println!("missed_times,{},{}", word.word, word.times);
+                println!("missed_points,{},{}", word.word, word.points);
+            }
+            for (i, found) in stats.progress.iter().enumerate() {
+                println!("progress,{},{}", i + 1, found);
+            }
+        }
+        Format::Json => println!("{}", serde_json::json!(stats)),
+    }
+    Ok(())
+}
+
+fn show(args: &GameArgs, games: &[GameRecord], round: usize) -> Result<(), Error> {
+    let game = match round.checked_sub(1).and_then(|i| games.get(i)) {
+        Some(game) => game,
+        None => {
+            return Err(Error::StringMessage(format!(
+                "no round {}; {} rounds have been played",
+                round,
+                games.len()
+            )))
+        }
+    };
+    let format = args.format();
+    match format {
+        Format::Json => {
+            println!("{}", serde_json::json!(game));
+            return Ok(());
+        }
+        Format::Text => {
+            println!(
+                "Round {}, played {} with the {} dice",
+                round,
+                format_date(game.time),
+                game.dice.as_deref().unwrap_or("unknown")
+            );
+            print!("{}", game.board);
+        }
+        Format::Csv => {}
+    }
+    let results: BTreeMap<String, RoundResult> = game
+        .players
+        .iter()
+        .map(|player| (player.name.clone(), player.result.clone()))
+        .collect();
+    let defs = load_definitions(args)?;
+    print_results(
+        format,
+        &defs,
+        &game.board,
+        game.scoring,
+        &args.name(),
+        &results,
+    );
+    Ok(())
+}
@@ -0,0 +1,275 @@
+use crate::args::{Format, GameArgs, JoinArgs};
+use crate::history::{save, Recorder};
+use crate::load_definitions;
+use crate::play::{print_result, print_result_csv, print_verdict, prompt};
+use boggle::io::Error;
+use boggle::protocol::*;
+use boggle::round::{format_time, RoundResult};
+use boggle::*;
+use std::collections::BTreeMap;
+use std::io::prelude::*;
+use std::io::{self, BufReader};
+use std::net::TcpStream;
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Something for the client to react to: a message from the server, or a line typed.
+enum Event {
+    Message(ServerMessage),
+    Disconnected,
+    Line(String),
+    EndOfInput,
+}
+
+/// Reads from the server and from stdin on threads of their own, passing everything to one
+/// channel so that neither has to wait for the other.
+fn spawn_events(stream: TcpStream) -> mpsc::Receiver<Event> {
+    let (tx, rx) = mpsc::channel();
+    let server = tx.clone();
+    thread::spawn(move || {
+        for line in BufReader::new(stream).lines() {
+            let message = match line {
+                Ok(line) => match serde_json::from_str(&line) {
+                    Ok(message) => message,
+                    Err(err) => ServerMessage::Error {
+                        message: format!("unexpected message from the server: {}", err),
+                    },
+                },
+                Err(_) => break,
+            };
+            if server.send(Event::Message(message)).is_err() {
+                return;
+            }
+        }
+        let _ = server.send(Event::Disconnected);
+    });
+    thread::spawn(move || {
+        for line in io::stdin().lock().lines() {
+            let line = match line {
+                Ok(line) => line,
+                Err(_) => break,
+            };
+            if tx.send(Event::Line(line)).is_err() {
+                return;
+            }
+        }
+        let _ = tx.send(Event::EndOfInput);
+    });
+    rx
+}
+
+fn send(stream: &mut TcpStream, message: &ClientMessage) -> Result<(), Error> {
+    let mut line =
+        serde_json::to_string(message).map_err(|err| Error::StringMessage(err.to_string()))?;
+    line.push('\n');
+    stream.write_all(line.as_bytes())?;
+    Ok(())
+}
+
+/// The round being played, as far as the client knows it.
+struct ClientRound {
+    board: Board,
+    deadline: Instant,
+    scoring: Scoring,
+    min_len: usize,
+    recorder: Recorder,
+}
+
+pub fn join(args: &GameArgs, options: &JoinArgs) -> Result<(), Error> {
+    let defs = load_definitions(args)?;
+    let name = args.name();
+    let history = args.history();
+    let stream = TcpStream::connect(&options.address)?;
+    let mut out = stream.try_clone()?;
+    send(
+        &mut out,
+        &ClientMessage::Join {
+            room: options.room.clone(),
+            name: name.clone(),
+        },
+    )?;
+    let events = spawn_events(stream);
+    let mut joined = false;
+    let mut round: Option<ClientRound> = None;
+    for event in events.iter() {
+        match event {
+            Event::Message(ServerMessage::Players { room, players }) => {
+                println!();
+                println!("Playing in {} with: {}", room, players.join(", "));
+                if !joined && players.contains(&name) {
+                    joined = true;
+                    println!("Press Enter to start a round, or type q to leave.");
+                }
+            }
+            Event::Message(ServerMessage::Start {
+                board,
+                millis,
+                scoring,
+                min_len,
+            }) => {
+                let left = Duration::from_millis(millis);
+                println!();
+                print!("{}", board);
+                println!(
+                    "You have {}. Enter one word per line; words need at least {} letters.",
+                    format_time(left),
+                    min_len
+                );
+                round = Some(ClientRound {
+                    board,
+                    deadline: Instant::now() + left,
+                    scoring,
+                    min_len,
+                    recorder: Recorder::new(board),
+                });
+            }
+            Event::Message(ServerMessage::Verdict { word, verdict }) => {
+                if let Some(round) = &round {
+                    print_verdict(round.scoring, &word, verdict);
+                }
+            }
+            Event::Message(ServerMessage::Progress { found }) => {
+                let counts: Vec<String> = found
+                    .iter()
+                    .filter(|(player, _)| **player != name)
+                    .map(|(player, count)| format!("{} {}", player, count))
+                    .collect();
+                if !counts.is_empty() {
+                    println!();
+                    println!("Found so far: {}", counts.join(", "));
+                }
+            }
+            Event::Message(ServerMessage::Results { results }) => {
+                println!();
+                println!("Time's up!");
+                if let Some(round) = round.take() {
+                    print_results(
+                        args.format(),
+                        &defs,
+                        &round.board,
+                        round.scoring,
+                        &name,
+                        &results,
+                    );
+                    let record = round.recorder.finish(
+                        &name,
+                        results
+                            .iter()
+                            .map(|(player, result)| (player.as_str(), result)),
+                        round.scoring,
+                        round.min_len,
+                    );
+                    save(history.as_deref(), &record)?;
+                }
+                println!("Press Enter to play again, or type q to leave.");
+            }
+            Event::Message(ServerMessage::Error { message }) => {
+                if !joined {
+                    return Err(Error::StringMessage(message));
+                }
+                println!("{}", message);
+            }
+            Event::Disconnected => {
+                return Err(Error::SimpleMessage("the server closed the connection"));
+            }
+            Event::Line(line) => {
+                let line = line.trim();
+                match &mut round {
+                    Some(round) if !line.is_empty() => {
+                        round.recorder.word(line);
+                        send(
+                            &mut out,
+                            &ClientMessage::Word {
+                                word: line.to_string(),
+                            },
+                        )?
+                    }
+                    Some(_) => {}
+                    None if line.is_empty() => send(&mut out, &ClientMessage::Start)?,
+                    None if line == "q" => break,
+                    None => println!("Wait for a round to start"),
+                }
+                // the reply to a word is printed when it arrives, so only prompt again after it
+                if line.is_empty() || round.is_none() {
+                    prompt(round.as_ref().map(|round| round.deadline))?;
+                }
+                continue;
+            }
+            Event::EndOfInput => break,
+        }
+        prompt(round.as_ref().map(|round| round.deadline))?;
+    }
+    Ok(())
+}
+
+/// Prints how everyone did, followed by the words of the player called `name`, or of the first
+/// player if they didn't play.
+pub fn print_results(
+    format: Format,
+    defs: &Definitions,
+    board: &Board,
+    scoring: Scoring,
+    name: &str,
+    results: &BTreeMap<String, RoundResult>,
+) {
+    let own = results.get(name).or_else(|| results.values().next());
+    let mut players: Vec<(&String, &RoundResult)> = results.iter().collect();
+    players.sort_by_key(|(_, result)| std::cmp::Reverse(result.score_with(scoring)));
+    match format {
+        Format::Text if players.len() > 1 => {
+            let width = players
+                .iter()
+                .map(|(player, _)| player.chars().count())
+                .max()
+                .unwrap_or(0)
+                .max("Player".len());
+            println!(
+                "{:<width$}  {:>5}  {:>9}  {:>5}",
+                "Player",
+                "Words",
+                "Cancelled",
+                "Score",
+                width = width
+            );
+            for (player, result) in players.iter() {
+                println!(
+                    "{:<width$}  {:>5}  {:>9}  {:>5}",
+                    player,
+                    result.correct.len(),
+                    result.cancelled.len(),
+                    result.score_with(scoring),
+                    width = width
+                );
+            }
+            println!("{}", "-".repeat(80));
+            if let Some(result) = own {
+                print_result(scoring, defs, None, board, result);
+            }
+        }
+        Format::Text => {
+            if let Some(result) = own {
+                print_result(scoring, defs, None, board, result);
+            }
+        }
+        Format::Csv => {
+            if let Some(result) = own {
+                print_result_csv(scoring, result);
+            }
+        }
+        Format::Json => {
+            let scores: BTreeMap<&String, u32> = players
+                .iter()
+                .map(|(player, result)| (*player, result.score_with(scoring)))
+                .collect();
+            println!(
+                "{}",
+                serde_json::json!({
+                    "board": board,
+                    "results": results,
+                    "scores": scores,
+                })
+            );
+        }
+    }
+}
@@ -0,0 +1,204 @@
+mod args;
+mod dict;
+mod history;
+mod join;
+mod play;
+mod practice;
+mod solve;
+mod stats;
+#[cfg(feature = "tui")]
+mod tui;
+
+use args::*;
+use boggle::filter::*;
+use boggle::io::*;
+use boggle::layered::*;
+use boggle::*;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+
+/// The dice set used when no other is chosen.
+const CLASSIC_DICE: &str = include_str!("../../../dice.txt");
+
+fn main() {
+    if let Err(err) = run() {
+        eprintln!("error: {}", err);
+        process::exit(1);
+    }
+}
+
+fn run() -> Result<(), Error> {
+    let cli = Cli::load()?;
+    // managing word lists, playing on a server, which has its own dictionary, and looking back on
+    // past rounds shouldn't need a dictionary or dice to load
+    match &cli.command {
+        Some(Command::Dict { command }) => dict::dict(&cli.game, command),
+        Some(Command::Join(args)) => join::join(&cli.game, args),
+        Some(Command::History { command }) => history::history(&cli.game, command),
+        Some(Command::Solve(args)) => solve::solve(&Game::load(&cli.game)?, args),
+        Some(Command::Stats(args)) => stats::stats(&Game::load(&cli.game)?, args),
+        Some(Command::Practice(args)) => practice::practice(&Game::load(&cli.game)?, args),
+        #[cfg(feature = "tui")]
+        None if cli.play.tui => tui::play(&Game::load(&cli.game)?, &cli.play),
+        None => play::play(&Game::load(&cli.game)?, &cli.play),
+    }
+}
+
+/// Everything loaded from the options that is needed to play.
+pub struct Game {
+    /// The dictionaries given with `--dict`.
+    pub base: Dict,
+    /// The base with the custom words added and the removed words taken out.
+    pub dict: Dict,
+    pub overlay: Option<Overlay>,
+    pub block: Dict,
+    pub defs: Definitions,
+    pub dice: DiceSet,
+    pub scoring: Scoring,
+    pub min_len: usize,
+    pub format: Format,
+    pub seed: Option<u64>,
+    /// The player's name, for the history log.
+    pub name: String,
+    pub history: Option<PathBuf>,
+}
+
+impl Game {
+    fn load(args: &GameArgs) -> Result<Self, Error> {
+        let base = load_base(args)?;
+        let overlay = Overlay::from_args(args);
+        Ok(Game {
+            dict: merge_overlay(args, &base, overlay.as_ref())?,
+            base,
+            overlay,
+            block: load_blocklist(args)?,
+            defs: load_definitions(args)?,
+            dice: load_dice(args)?,
+            scoring: args.scoring(),
+            min_len: args.min_len(),
+            format: args.format(),
+            seed: args.seed,
+            name: args.name(),
+            history: args.history(),
+        })
+    }
+
+    pub fn filtered(&self) -> FilteredDict<'_> {
+        self.filter(&self.dict)
+    }
+
+    /// Filters `dict` the way the game's own dictionary is, for a copy with words added to it.
+    pub fn filter<'a>(&'a self, dict: &'a Dict) -> FilteredDict<'a> {
+        let mut dict = FilteredDict::new(dict, &self.block);
+        dict.min_len = self.min_len;
+        dict
+    }
+
+    pub fn rng(&self) -> StdRng {
+        match self.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        }
+    }
+}
+
+/// Where the words added to and removed from the `--dict` dictionaries are saved.
+pub struct Overlay {
+    pub added: PathBuf,
+    pub removed: PathBuf,
+}
+
+impl Overlay {
+    pub fn from_args(args: &GameArgs) -> Option<Self> {
+        Some(Overlay {
+            added: args.custom()?,
+            removed: args.removed()?,
+        })
+    }
+
+    pub fn read<'a>(&self, base: &'a Dict) -> Result<LayeredDict<'a>, Error> {
+        read_overlay(base, &self.added, &self.removed)
+    }
+
+    /// Saves the overlay, creating its directories if needed.
+    pub fn write(&self, dict: &LayeredDict) -> Result<(), Error> {
+        for path in [&self.added, &self.removed].iter() {
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir)?;
+            }
+        }
+        write_overlay(dict, &self.added, &self.removed)
+    }
+}
+
+/// Reads and combines the dictionaries given with `--dict`.
+pub fn load_base(args: &GameArgs) -> Result<Dict, Error> {
+    let mut dict = Dict::new();
+    for path in args.dicts.iter() {
+        dict |= &read_dict(path)?;
+    }
+    Ok(dict)
+}
+
+/// Reads the dictionary to play with: the base with the custom words applied.
+pub fn load_dicts(args: &GameArgs) -> Result<Dict, Error> {
+    merge_overlay(args, &load_base(args)?, Overlay::from_args(args).as_ref())
+}
+
+fn merge_overlay(args: &GameArgs, base: &Dict, overlay: Option<&Overlay>) -> Result<Dict, Error> {
+    if args.dicts.is_empty() && !overlay.is_some_and(|overlay| overlay.added.is_file()) {
+        return Err(Error::SimpleMessage(
+            "no dictionary found; pass one with --dict or put it in the data directory",
+        ));
+    }
+    match overlay {
+        Some(overlay) => Ok(overlay.read(base)?.merged()),
+        None => Ok(base.clone()),
+    }
+}
+
+pub fn load_blocklist(args: &GameArgs) -> Result<Dict, Error> {
+    match args
+        .blocklist
+        .clone()
+        .or_else(|| find_data_file("dictionaries/blocklist.txt"))
+    {
+        Some(path) => read_dict(path),
+        None => Ok(Dict::new()),
+    }
+}
+
+fn load_definitions(args: &GameArgs) -> Result<Definitions, Error> {
+    let path = args.definitions.clone().or_else(|| {
+        find_data_file("dictionaries/definitions.json")
+            .or_else(|| find_data_file("dictionaries/definitions.txt"))
+    });
+    match path {
+        Some(path) if path.extension().is_some_and(|ext| ext == "json") => {
+            read_definitions_json(path)
+        }
+        Some(path) => read_definitions(path),
+        None => Ok(Definitions::default()),
+    }
+}
+
+/// Reads the dice set from a file if one exists at the given path, and otherwise looks it up by
+/// name in the data directory. `classic` is built in.
+fn load_dice(args: &GameArgs) -> Result<DiceSet, Error> {
+    let name = args.dice.as_deref().unwrap_or("classic");
+    let mut set = if Path::new(name).is_file() {
+        read_dice_set(name)?
+    } else if let Some(path) = find_data_file(&format!("dice/{}.txt", name)) {
+        read_dice_set(path)?
+    } else if name == "classic" {
+        parse_dice_set(CLASSIC_DICE)?
+    } else {
+        return Err(Error::StringMessage(format!("no dice set named {}", name)));
+    };
+    // sets without a name header go by the name they were chosen with
+    set.name.get_or_insert_with(|| name.to_string());
+    Ok(set)
+}
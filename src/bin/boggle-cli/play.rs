@@ -0,0 +1,402 @@
+use crate::args::{Format, PlayArgs};
+use crate::history::Recorder;
+use crate::Game;
+use boggle::filter::*;
+use boggle::hint::*;
+use boggle::io::Error;
+use boggle::round::*;
+use boggle::*;
+use std::borrow::Cow;
+use std::io;
+use std::io::prelude::*;
+use std::mem;
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+const WARNING: Duration = Duration::from_secs(30);
+
+/// Reads stdin on its own thread, so that a round can end while waiting for a word.
+fn spawn_stdin() -> mpsc::Receiver<io::Result<String>> {
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        for line in io::stdin().lock().lines() {
+            if tx.send(line).is_err() {
+                break;
+            }
+        }
+    });
+    rx
+}
+
+pub fn prompt(deadline: Option<Instant>) -> io::Result<()> {
+    if let Some(deadline) = deadline {
+        print!(
+            "[{}] ",
+            format_time(deadline.saturating_duration_since(Instant::now()))
+        );
+        io::stdout().flush()?;
+    }
+    Ok(())
+}
+
+pub fn play(game: &Game, options: &PlayArgs) -> Result<(), Error> {
+    let mut rng = game.rng();
+    let timer = options.timer();
+    let lines = spawn_stdin();
+    let mut words = Cow::Borrowed(&game.dict);
+    // the last round played, to score again if one of its words is added
+    let mut last: Option<Round> = None;
+    // whether the last round ran out of time
+    let mut timed_out = false;
+    loop {
+        print!("Play? (Y/n, or +WORD to add a word to the dictionary) ");
+        io::stdout().flush()?;
+        let buf = match lines.recv() {
+            Ok(line) => line?,
+            Err(_) => break,
+        };
+        let late = mem::replace(&mut timed_out, false);
+        match buf.trim() {
+            "" | "y" | "Y" => {}
+            "n" | "N" => break,
+            line if line.starts_with('+') => {
+                let word = line[1..].trim();
+                match accept(game, &mut words, word)? {
+                    Some(message) => println!("{}", message),
+                    None => {
+                        println!("{} can't be added to the dictionary", word);
+                        continue;
+                    }
+                }
+                if let Some(round) = last.as_ref().filter(|round| played(round, word)) {
+                    println!("Scoring the last round again:");
+                    let dict = &game.filter(&words);
+                    print_round(game, dict, round, &round.result_filtered(dict));
+                }
+                continue;
+            }
+            word if late => {
+                println!("{} came in after time was up", word);
+                continue;
+            }
+            _ => {
+                println!("Please answer y or n, or +WORD to add a word to the dictionary");
+                continue;
+            }
+        }
+        let dict = &game.filter(&words);
+        let board = roll(&game.dice.dice, &mut rng);
+        print!("{}", board);
+        match timer {
+            Some(timer) => println!(
+                "You have {}. Enter one word per line, ? for a hint (?? and ??? for bigger ones), or a blank line to finish early.",
+                format_time(timer)
+            ),
+            None => println!("Enter one word per line, ? for a hint (?? and ??? for bigger ones), or a blank line to finish."),
+        }
+        let mut round = Round::new(board);
+        let mut recorder = Recorder::new(board);
+        let mut checker = Checker::new(&board, dict);
+        let shown = dict.shown_words(&board);
+        let round_timer = timer.map(Timer::new);
+        let deadline = round_timer.map(|timer| timer.deadline());
+        let mut warned = timer.is_some_and(|timer| timer <= WARNING);
+        prompt(deadline)?;
+        loop {
+            let line = match round_timer {
+                None => lines.recv().ok(),
+                Some(round_timer) => {
+                    let deadline = round_timer.deadline();
+                    let left = round_timer.left_at(Instant::now());
+                    if left.is_zero() {
+                        println!();
+                        println!("Time's up!");
+                        // words finished after the buzzer don't count
+                        while lines.try_recv().is_ok() {}
+                        timed_out = true;
+                        break;
+                    }
+                    if !warned && left <= WARNING {
+                        warned = true;
+                        println!();
+                        println!("30 seconds left!");
+                        prompt(Some(deadline))?;
+                    }
+                    // wake up at each whole minute, for the warning, or when time runs out
+                    let to_minute = Duration::from_nanos((left.as_nanos() % 60_000_000_000) as u64);
+                    let mut wait = if to_minute.is_zero() {
+                        Duration::from_secs(60)
+                    } else {
+                        to_minute
+                    };
+                    if !warned {
+                        wait = wait.min(left - WARNING);
+                    }
+                    match lines.recv_timeout(wait.min(left)) {
+                        Ok(line) => Some(line),
+                        Err(mpsc::RecvTimeoutError::Timeout) => {
+                            let left = deadline.saturating_duration_since(Instant::now());
+                            let secs = left.as_secs() + (left.subsec_nanos() > 0) as u64;
+                            if secs > 0 && secs % 60 == 0 {
+                                println!();
+                                println!("{} left", format_time(left));
+                                prompt(Some(deadline))?;
+                            }
+                            continue;
+                        }
+                        Err(mpsc::RecvTimeoutError::Disconnected) => None,
+                    }
+                }
+            };
+            let line = match line {
+                Some(line) => line?,
+                None => break,
+            };
+            let trimmed = line.trim();
+            if trimmed.is_empty() {
+                break;
+            }
+            if trimmed.chars().all(|c| c == '?') {
+                let found: Dict = round
+                    .words
+                    .iter()
+                    .flat_map(|w| w.parse::<BString>())
+                    .collect();
+                let hints = Hints::new(board, &shown, &found);
+                let hint = match trimmed.len() {
+                    1 => Some(hints.counts()),
+                    2 => hints.first_letters(2, &mut rng),
+                    _ => hints.starting_tile(&mut rng),
+                };
+                match hint {
+                    Some(hint) => println!("{}", hint),
+                    None => println!("No words left to find"),
+                }
+            } else {
+                let verdict = match &round_timer {
+                    Some(round_timer) => checker.check_at(trimmed, round_timer, Instant::now()),
+                    None => Some(checker.check(trimmed)),
+                };
+                // a word entered as time ran out is dropped, and the round ends at the top of
+                // the loop
+                let verdict = match verdict {
+                    Some(verdict) => verdict,
+                    None => continue,
+                };
+                round.words.push(String::from(trimmed));
+                recorder.word(trimmed);
+                if options.feedback {
+                    print_verdict(game.scoring, trimmed, verdict);
+                }
+            }
+            prompt(deadline)?;
+        }
+        let result = checker.finish();
+        print_round(game, dict, &round, &result);
+        recorder.save(game, &words, &result)?;
+        last = Some(round);
+    }
+    Ok(())
+}
+
+fn print_round(game: &Game, dict: &FilteredDict, round: &Round, result: &RoundResult) {
+    match game.format {
+        Format::Text => print_result(game.scoring, &game.defs, Some(dict), &round.board, result),
+        Format::Csv => print_result_csv(game.scoring, result),
+        Format::Json => println!(
+            "{}",
+            serde_json::json!({
+                "board": round.board,
+                "words": round.words,
+                "result": result,
+                "score": result.score_with(game.scoring),
+                "max_score": result.max_score_with(game.scoring),
+            })
+        ),
+    }
+}
+
+/// Whether `word` was entered in `round`, in any case.
+pub fn played(round: &Round, word: &str) -> bool {
+    let word = word.parse::<BString>().ok();
+    round
+        .words
+        .iter()
+        .any(|w| w.parse::<BString>().ok() == word)
+}
+
+pub fn print_verdict(scoring: Scoring, word: &str, verdict: Verdict) {
+    match verdict {
+        Verdict::Correct => println!("Correct: {}", scoring.score(word)),
+        Verdict::NotPresent => println!("Repeated or not on the board"),
+        Verdict::TooShort => println!("Too short"),
+        Verdict::NotWord => println!("Not in the dictionary"),
+        Verdict::NotBword => println!("Not possible in Boggle"),
+        Verdict::Blocked => println!("Not allowed in this game"),
+    }
+}
+
+/// Prints the words of a round by category. Without a dictionary, there are no suggestions for
+/// words that weren't in it.
+pub fn print_result(
+    scoring: Scoring,
+    defs: &Definitions,
+    dict: Option<&FilteredDict>,
+    board: &Board,
+    result: &RoundResult,
+) {
+    if !result.correct.is_empty() {
+        println!("Correct:");
+        for word in result.correct.iter() {
+            println!("{}: {}", word, scoring.score(word));
+        }
+    }
+    if !result.cancelled.is_empty() {
+        println!("Also found by another player:");
+        for word in result.cancelled.iter() {
+            println!("{}", word);
+        }
+    }
+    if !result.not_present.is_empty() {
+        println!("Repeated or not on the board:");
+        for word in result.not_present.iter() {
+            println!("{}", word);
+        }
+    }
+    if !result.too_short.is_empty() {
+        println!("Too short:");
+        for word in result.too_short.iter() {
+            println!("{}", word);
+        }
+    }
+    if !result.not_word.is_empty() {
+        println!("Not in the dictionary:");
+        let on_board = dict.map(|dict| (dict, dict.playable_words(board)));
+        for word in result.not_word.iter() {
+            match definition(defs, word) {
+                Some(def) => println!("{} - {}", word, def),
+                None => println!("{}", word),
+            }
+            if let Some((dict, on_board)) = &on_board {
+                let suggestions = suggest(dict, on_board, word);
+                if !suggestions.is_empty() {
+                    println!("  did you mean: {}?", suggestions.join(", "));
+                }
+            }
+        }
+    }
+    if !result.not_bword.is_empty() {
+        println!("Not possible in Boggle:");
+        for word in result.not_bword.iter() {
+            println!("{}", word);
+        }
+    }
+    if !result.blocked.is_empty() {
+        println!("Not allowed in this game:");
+        for word in result.blocked.iter() {
+            println!("{}", word);
+        }
+    }
+    println!("{}", "-".repeat(80));
+    println!("Score: {}", result.score_with(scoring));
+    println!("{}", "-".repeat(80));
+    let mut other_words: Vec<String> = result
+        .missed
+        .words()
+        .into_iter()
+        .map(|w| w.to_string())
+        .collect();
+    other_words.sort_unstable_by_key(|w| -(w.len() as i8));
+    if !other_words.is_empty() {
+        println!("Some other words on the board:");
+        for word in other_words.into_iter().take(16) {
+            match definition(defs, &word) {
+                Some(def) => println!("{}: {} - {}", word, scoring.score(&word), def),
+                None => println!("{}: {}", word, scoring.score(&word)),
+            }
+        }
+    }
+}
+
+pub fn print_result_csv(scoring: Scoring, result: &RoundResult) {
+    println!("word,result,score");
+    let categories = [
+        ("correct", &result.correct),
+        ("cancelled", &result.cancelled),
+        ("not_present", &result.not_present),
+        ("too_short", &result.too_short),
+        ("not_word", &result.not_word),
+        ("not_bword", &result.not_bword),
+        ("blocked", &result.blocked),
+    ];
+    for (category, words) in categories.iter() {
+        for word in words.iter() {
+            let score = match *category {
+                "correct" => scoring.score(word),
+                _ => 0,
+            };
+            println!("{},{},{}", csv_field(word), category, score);
+        }
+    }
+    for word in result.missed.iter() {
+        println!("{},missed,0", word);
+    }
+}
+
+/// Quotes a CSV field if it holds a comma, quote or line break, doubling any quotes.
+pub fn csv_field(field: &str) -> Cow<'_, str> {
+    if field.contains(&[',', '"', '\n', '\r'][..]) {
+        Cow::Owned(format!("\"{}\"", field.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(field)
+    }
+}
+
+/// Adds a challenged word to `dict`, and saves it to the custom dictionary so that it is accepted
+/// in later games too. Returns a message saying where it went, or `None` if it can't be a word in
+/// this game.
+pub fn accept(game: &Game, dict: &mut Cow<Dict>, word: &str) -> Result<Option<String>, Error> {
+    let bword = match word.parse::<BString>() {
+        Ok(bword) if word.len() >= game.min_len => bword,
+        _ => return Ok(None),
+    };
+    dict.to_mut().insert(&bword);
+    match &game.overlay {
+        Some(overlay) => {
+            let mut dict = overlay.read(&game.base)?;
+            dict.insert(&bword);
+            overlay.write(&dict)?;
+            Ok(Some(format!(
+                "Added {} to {}",
+                word,
+                overlay.added.display()
+            )))
+        }
+        None => Ok(Some(format!("Added {} for this session", word))),
+    }
+}
+
+/// Suggests words close to `word`, preferring those on the board.
+fn suggest(dict: &FilteredDict, on_board: &Dict, word: &str) -> Vec<String> {
+    let bword = match word.parse::<BString>() {
+        Ok(bword) => bword,
+        Err(_) => return Vec::new(),
+    };
+    let max_dist = if bword.len() <= 4 { 1 } else { 2 };
+    let mut suggestions = on_board.fuzzy(&bword, max_dist);
+    if suggestions.is_empty() {
+        suggestions = dict.allow().fuzzy(&bword, max_dist);
+    }
+    suggestions
+        .into_iter()
+        .filter(|(w, _)| dict.shows(w))
+        .take(3)
+        .map(|(w, _)| w.to_string())
+        .collect()
+}
+
+pub fn definition<'a>(defs: &'a Definitions, word: &str) -> Option<&'a str> {
+    let bword = word.parse::<BString>().ok()?;
+    defs.get(&bword).map(String::as_str)
+}
@@ -0,0 +1,157 @@
+use crate::args::PracticeArgs;
+use crate::history::{format_date, now};
+use crate::play::{definition, print_verdict};
+use crate::Game;
+use boggle::io::*;
+use boggle::round::*;
+use boggle::trainer::*;
+use boggle::*;
+use std::fs;
+use std::io;
+use std::io::prelude::*;
+use std::path::Path;
+
+/// How many boards to roll for each round, keeping the one with the most words that are due.
+const TRIES: usize = 50;
+
+fn read_schedule(path: &Path) -> Result<Schedule, Error> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Schedule::default()),
+        Err(err) => return Err(err.into()),
+    };
+    serde_json::from_str(&text)
+        .map_err(|err| Error::StringMessage(format!("{}: {}", path.display(), err)))
+}
+
+fn write_schedule(path: &Path, schedule: &Schedule) -> Result<(), Error> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let json =
+        serde_json::to_string(schedule).map_err(|err| Error::StringMessage(err.to_string()))?;
+    fs::write(path, json)?;
+    Ok(())
+}
+
+/// Plays untimed rounds on boards made to contain the words that are due, until none are left or
+/// the player stops. Practice rounds aren't saved to the history log, so that boards chosen for
+/// their words don't count towards the player's statistics.
+pub fn practice(game: &Game, options: &PracticeArgs) -> Result<(), Error> {
+    let path = options.schedule().ok_or(Error::SimpleMessage(
+        "no data directory for the practice schedule; pass --schedule",
+    ))?;
+    let mut schedule = read_schedule(&path)?;
+    if let Some(history) = &game.history {
+        let games = read_history(history)?;
+        let added = schedule.add_missed(&games, &game.name, options.min_missed, now());
+        if added > 0 {
+            println!("Added {} words you often miss", added);
+            write_schedule(&path, &schedule)?;
+        }
+    }
+    let dict = game.filtered();
+    let mut rng = game.rng();
+    let mut lines = io::stdin().lock().lines();
+    loop {
+        // the words as the schedule has them, to review them by, and as they are played
+        let mut due = Vec::new();
+        let mut unspellable = Vec::new();
+        for key in schedule.due(now()) {
+            match key.parse::<BString>() {
+                Ok(word) if can_spell(&word, &game.dice.dice) => due.push((key.to_string(), word)),
+                _ => unspellable.push(key.to_string()),
+            }
+        }
+        // words the dice can't spell would never come up, and would stay due for good
+        if !unspellable.is_empty() {
+            let tomorrow = now() + DAY;
+            for key in unspellable.iter() {
+                schedule.postpone(key, tomorrow);
+            }
+            println!(
+                "Put off {} words the {} dice can't spell until tomorrow",
+                unspellable.len(),
+                game.dice.name.as_deref().unwrap_or("chosen")
+            );
+            write_schedule(&path, &schedule)?;
+        }
+        if due.is_empty() {
+            match schedule.next_due() {
+                Some(time) => println!("Nothing to practise until {} (UTC)", format_date(time)),
+                None => println!(
+                    "Nothing to practise yet; words missed in {} or more rounds are added from the history",
+                    options.min_missed
+                ),
+            }
+            return Ok(());
+        }
+        let words: Vec<BString> = due.iter().map(|(_, word)| word.clone()).collect();
+        let (board, targets) = practice_board(&words, &game.dice.dice, TRIES, &mut rng)
+            .ok_or_else(|| {
+                Error::StringMessage(format!(
+                    "the {} dice can't spell any of the words due",
+                    game.dice.name.as_deref().unwrap_or("chosen")
+                ))
+            })?;
+        print!("{}", board);
+        println!(
+            "{} of the words you're practising are on this board:",
+            targets.len()
+        );
+        for word in targets.iter() {
+            let text = word.to_string();
+            match (definition(&game.defs, &text), word.split_first()) {
+                (Some(def), _) => println!("  {} letters: {}", text.len(), def),
+                (None, Some((first, _))) => {
+                    println!("  {} letters, starting with {}", text.len(), first)
+                }
+                (None, None) => {}
+            }
+        }
+        println!("Enter one word per line, or a blank line to finish.");
+
+        let mut checker = Checker::new(&board, &dict);
+        for line in lines.by_ref() {
+            let line = line?;
+            let word = line.trim();
+            if word.is_empty() {
+                break;
+            }
+            print_verdict(game.scoring, word, checker.check(word));
+        }
+        let result = checker.finish();
+        let found: Dict = result
+            .correct
+            .iter()
+            .flat_map(|word| word.parse::<BString>())
+            .collect();
+        let now = now();
+        for (key, word) in due.iter().filter(|(_, word)| targets.contains(word)) {
+            let word_found = found.contains(word);
+            let text = word.to_string();
+            schedule.review(key, word_found, now);
+            let days = match schedule.words.get(key) {
+                Some(card) => (card.due - now) / DAY,
+                None => continue,
+            };
+            match (word_found, days) {
+                (true, 1) => println!("Found {}; it comes up again tomorrow", text),
+                (true, days) => println!("Found {}; it comes up again in {} days", text, days),
+                (false, _) => println!("Missed {}; it comes up again next time", text),
+            }
+        }
+        println!("Score: {}", result.score_with(game.scoring));
+        write_schedule(&path, &schedule)?;
+
+        print!("Practise another board? (Y/n) ");
+        io::stdout().flush()?;
+        let answer = match lines.next() {
+            Some(line) => line?,
+            None => return Ok(()),
+        };
+        if !matches!(answer.trim(), "" | "y" | "Y") {
+            return Ok(());
+        }
+    }
+}
@@ -0,0 +1,114 @@
+use crate::args::{Format, SolveArgs, SortOrder};
+use crate::play::csv_field;
+use crate::Game;
+use boggle::io::Error;
+use boggle::*;
+use std::fs;
+use std::io;
+use std::io::prelude::*;
+
+/// A word on the board, with what is printed about it.
+struct Solution {
+    word: String,
+    score: u32,
+    path: Vec<(usize, usize)>,
+}
+
+pub fn solve(game: &Game, args: &SolveArgs) -> Result<(), Error> {
+    let text = if !args.board.is_empty() {
+        args.board.join(" ")
+    } else if let Some(path) = &args.file {
+        fs::read_to_string(path)?
+    } else {
+        let mut text = String::new();
+        io::stdin().read_to_string(&mut text)?;
+        text
+    };
+    let board: Board = text
+        .parse()
+        .map_err(|err| Error::StringMessage(format!("invalid board: {}", err)))?;
+
+    let mut words = game.filtered().playable_words(&board);
+    if let Some(pattern) = &args.pattern {
+        words = words.matching(pattern);
+    }
+    let mut solutions: Vec<Solution> = words
+        .iter()
+        .map(|word| Solution {
+            score: game.scoring.score(&word.to_string()),
+            path: board.find_path(&word).unwrap(),
+            word: word.to_string(),
+        })
+        .filter(|s| args.min_score.is_none_or(|min| s.score >= min))
+        .filter(|s| args.max_len.is_none_or(|max| s.word.len() <= max))
+        .collect();
+    // the words come out of the dictionary in alphabetical order, and the sorts are stable
+    match args.sort {
+        SortOrder::Score => solutions.sort_by_key(|s| std::cmp::Reverse(s.score)),
+        SortOrder::Length => solutions.sort_by_key(|s| std::cmp::Reverse(s.word.len())),
+        SortOrder::Alpha => {}
+    }
+    if args.reverse {
+        solutions.reverse();
+    }
+    solutions.truncate(args.limit.unwrap_or(usize::MAX));
+
+    match game.format {
+        Format::Text => {
+            for s in solutions.iter() {
+                if args.paths {
+                    println!("{}\t{}\t{}", s.word, s.score, format_path(&s.path));
+                } else {
+                    println!("{}\t{}", s.word, s.score);
+                }
+            }
+        }
+        Format::Csv => {
+            if args.paths {
+                println!("word,score,path");
+            } else {
+                println!("word,score");
+            }
+            for s in solutions.iter() {
+                if args.paths {
+                    println!(
+                        "{},{},{}",
+                        s.word,
+                        s.score,
+                        csv_field(&format_path(&s.path))
+                    );
+                } else {
+                    println!("{},{}", s.word, s.score);
+                }
+            }
+        }
+        Format::Json => {
+            let words: Vec<_> = solutions
+                .iter()
+                .map(|s| {
+                    let mut value = serde_json::json!({ "word": s.word, "score": s.score });
+                    if args.paths {
+                        value["path"] = serde_json::json!(s.path);
+                    }
+                    value
+                })
+                .collect();
+            println!(
+                "{}",
+                serde_json::json!({
+                    "board": board,
+                    "words": words,
+                    "score": solutions.iter().map(|s| s.score).sum::<u32>(),
+                })
+            );
+        }
+    }
+    Ok(())
+}
+
+fn format_path(path: &[(usize, usize)]) -> String {
+    path.iter()
+        .map(|(row, col)| format!("{},{}", row, col))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
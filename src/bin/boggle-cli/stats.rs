@@ -0,0 +1,182 @@
+use crate::args::{Format, StatsArgs};
+use crate::Game;
+use boggle::io::Error;
+use boggle::*;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+/// Percentiles of the values seen over all the boards.
+struct Summary {
+    min: u32,
+    p10: u32,
+    p25: u32,
+    median: u32,
+    p75: u32,
+    p90: u32,
+    max: u32,
+    mean: f64,
+}
+
+impl Summary {
+    fn new(mut values: Vec<u32>) -> Self {
+        values.sort_unstable();
+        let at = |p: usize| values[(values.len() - 1) * p / 100];
+        Summary {
+            min: at(0),
+            p10: at(10),
+            p25: at(25),
+            median: at(50),
+            p75: at(75),
+            p90: at(90),
+            max: at(100),
+            mean: values.iter().map(|&v| v as f64).sum::<f64>() / values.len() as f64,
+        }
+    }
+
+    fn fields(&self) -> [(&'static str, String); 8] {
+        [
+            ("min", self.min.to_string()),
+            ("p10", self.p10.to_string()),
+            ("p25", self.p25.to_string()),
+            ("median", self.median.to_string()),
+            ("p75", self.p75.to_string()),
+            ("p90", self.p90.to_string()),
+            ("max", self.max.to_string()),
+            ("mean", format!("{:.2}", self.mean)),
+        ]
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "min": self.min,
+            "p10": self.p10,
+            "p25": self.p25,
+            "median": self.median,
+            "p75": self.p75,
+            "p90": self.p90,
+            "max": self.max,
+            "mean": self.mean,
+        })
+    }
+}
+
+pub fn stats(game: &Game, args: &StatsArgs) -> Result<(), Error> {
+    if args.boards == 0 {
+        return Err(Error::SimpleMessage("--boards must be at least 1"));
+    }
+    let seed = game.seed.unwrap_or_else(rand::random);
+    let mut rng = StdRng::seed_from_u64(seed);
+    let dict = game.filtered();
+
+    let mut word_counts = Vec::with_capacity(args.boards);
+    let mut scores = Vec::with_capacity(args.boards);
+    // the number of boards each word was found on
+    let mut found: Dict<u32> = Dict::default();
+    for _ in 0..args.boards {
+        let words = dict.playable_words(&roll(&game.dice.dice, &mut rng));
+        word_counts.push(words.len() as u32);
+        let mut score = 0;
+        for word in words.iter() {
+            score += game.scoring.score(&word.to_string());
+            match found.get_mut(&word) {
+                Some(boards) => *boards += 1,
+                None => {
+                    found.insert_value(&word, 1);
+                }
+            }
+        }
+        scores.push(score);
+    }
+    let empty = word_counts.iter().filter(|&&n| n == 0).count() as f64 / args.boards as f64;
+    let words = Summary::new(word_counts);
+    let score = Summary::new(scores);
+
+    let mut found: Vec<(String, u32)> = found
+        .entries()
+        .map(|(word, &boards)| (word.to_string(), boards))
+        .collect();
+    found.sort_by_key(|(word, _)| std::cmp::Reverse(word.len()));
+    let longest: Vec<(String, u32)> = found.iter().take(args.top).cloned().collect();
+    found.sort_by_key(|&(_, boards)| std::cmp::Reverse(boards));
+    found.truncate(args.top);
+    let common = found;
+    let fraction = |boards: u32| boards as f64 / args.boards as f64;
+
+    match game.format {
+        Format::Text => {
+            println!(
+                "{} boards rolled with {} dice, seed {}",
+                args.boards,
+                game.dice.name.as_deref().unwrap_or("unnamed"),
+                seed
+            );
+            println!("Boards with no words: {:.1}%", empty * 100.0);
+            println!();
+            print!("{:8}", "");
+            for (name, _) in words.fields().iter() {
+                print!("{:>8}", name);
+            }
+            println!();
+            for (name, summary) in [("Words", &words), ("Score", &score)].iter() {
+                print!("{:8}", name);
+                for (_, value) in summary.fields().iter() {
+                    print!("{:>8}", value);
+                }
+                println!();
+            }
+            println!();
+            println!("Longest words:");
+            for (word, boards) in longest.iter() {
+                println!("{:20}{:>4} letters, on {} boards", word, word.len(), boards);
+            }
+            println!();
+            println!("Most common words:");
+            for (word, boards) in common.iter() {
+                println!("{:20}{:>6.1}%", word, fraction(*boards) * 100.0);
+            }
+        }
+        Format::Csv => {
+            println!("section,name,value");
+            println!("boards,count,{}", args.boards);
+            println!("boards,seed,{}", seed);
+            println!("boards,empty,{}", empty);
+            for (section, summary) in [("words", &words), ("score", &score)].iter() {
+                for (name, value) in summary.fields().iter() {
+                    println!("{},{},{}", section, name, value);
+                }
+            }
+            for (word, boards) in longest.iter() {
+                println!("longest,{},{}", word, boards);
+            }
+            for (word, boards) in common.iter() {
+                println!("common,{},{}", word, fraction(*boards));
+            }
+        }
+        Format::Json => {
+            println!(
+                "{}",
+                serde_json::json!({
+                    "boards": args.boards,
+                    "dice": game.dice.name,
+                    "seed": seed,
+                    "empty": empty,
+                    "words": words.to_json(),
+                    "score": score.to_json(),
+                    "longest": longest
+                        .iter()
+                        .map(|(word, boards)| serde_json::json!({ "word": word, "boards": boards }))
+                        .collect::<Vec<_>>(),
+                    "common": common
+                        .iter()
+                        .map(|(word, boards)| serde_json::json!({
+                            "word": word,
+                            "boards": boards,
+                            "fraction": fraction(*boards),
+                        }))
+                        .collect::<Vec<_>>(),
+                })
+            );
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,482 @@
+use crate::args::PlayArgs;
+use crate::history::Recorder;
+use crate::play::{accept, definition, played};
+use crate::Game;
+use boggle::hint::*;
+use boggle::io::Error;
+use boggle::matcher::PathMatcher;
+use boggle::round::*;
+use boggle::*;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Alignment, Constraint, Layout, Rect};
+use ratatui::style::{Modifier, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, List, ListItem, Paragraph, Tabs};
+use ratatui::{DefaultTerminal, Frame};
+use std::borrow::Cow;
+use std::time::{Duration, Instant};
+
+/// How often the screen is redrawn while waiting for a key, to keep the timer moving.
+const TICK: Duration = Duration::from_millis(100);
+
+const TABS: [&str; 3] = ["Found", "Missed", "Rejected"];
+
+/// Plays rounds full screen until the player quits.
+pub fn play(game: &Game, options: &PlayArgs) -> Result<(), Error> {
+    let mut terminal = ratatui::init();
+    let result = play_rounds(&mut terminal, game, options);
+    ratatui::restore();
+    result
+}
+
+fn play_rounds(
+    terminal: &mut DefaultTerminal,
+    game: &Game,
+    options: &PlayArgs,
+) -> Result<(), Error> {
+    let mut rng = game.rng();
+    let mut words = Cow::Borrowed(&game.dict);
+    let mut live = game.filtered().hide(game.dict.clone());
+    loop {
+        let board = roll(&game.dice.dice, &mut rng);
+        let dict = game.filter(&words);
+        let mut state = RoundState {
+            board,
+            round: Round::new(board),
+            recorder: Recorder::new(board),
+            checker: Checker::new(&board, &dict),
+            shown: dict.shown_words(&board),
+            found: Vec::new(),
+            input: String::new(),
+            matcher: PathMatcher::new(&board, &live),
+            message: None,
+            hints_asked: 0,
+            deadline: options.timer().map(|timer| Instant::now() + timer),
+            feedback: options.feedback,
+        };
+        if !state.play(terminal, game, &mut rng)? {
+            return Ok(());
+        }
+        let result = state.checker.finish();
+        state.recorder.save(game, &words, &result)?;
+        let round = state.round;
+        let mut screen = ResultsScreen::new(game, &result);
+        let before = words.len();
+        if !screen.show(terminal, game, &mut words, &round)? {
+            return Ok(());
+        }
+        if words.len() != before {
+            live = game.filter(&words).hide(words.clone().into_owned());
+        }
+    }
+}
+
+/// A round in progress.
+struct RoundState<'a> {
+    board: Board,
+    round: Round,
+    recorder: Recorder,
+    checker: Checker<'a>,
+    /// The words that may be given as hints.
+    shown: Dict,
+    /// The words that scored, with their scores, most recent last.
+    found: Vec<(String, u32)>,
+    input: String,
+    /// Follows the input across the board.
+    matcher: PathMatcher<'a>,
+    /// Feedback on the last word entered, or a hint.
+    message: Option<Line<'static>>,
+    /// How many hints have been asked for in a row.
+    hints_asked: usize,
+    deadline: Option<Instant>,
+    /// Whether to say how each word did as it is entered, rather than only at the end.
+    feedback: bool,
+}
+
+impl RoundState<'_> {
+    /// Plays the round, returning `false` if the player quits the game.
+    fn play(
+        &mut self,
+        terminal: &mut DefaultTerminal,
+        game: &Game,
+        rng: &mut impl rand::Rng,
+    ) -> Result<bool, Error> {
+        loop {
+            if self
+                .deadline
+                .is_some_and(|deadline| Instant::now() >= deadline)
+            {
+                return Ok(true);
+            }
+            terminal.draw(|frame| self.draw(frame))?;
+            if !event::poll(TICK)? {
+                continue;
+            }
+            let key = match event::read()? {
+                Event::Key(key) if key.kind == KeyEventKind::Press => key,
+                _ => continue,
+            };
+            if key.code != KeyCode::Char('?') {
+                self.hints_asked = 0;
+            }
+            match key.code {
+                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    return Ok(false)
+                }
+                KeyCode::Esc => return Ok(true),
+                KeyCode::Char('?') => self.hint(rng),
+                KeyCode::Char(c) if c.is_ascii_alphabetic() => {
+                    self.input.push(c.to_ascii_lowercase())
+                }
+                KeyCode::Backspace => {
+                    self.input.pop();
+                }
+                KeyCode::Enter if !self.input.is_empty() => self.submit(game),
+                _ => {}
+            }
+            match self.typed() {
+                Some(word) => {
+                    self.matcher.set(&word);
+                }
+                None => self.matcher.clear(),
+            }
+        }
+    }
+
+    /// The input as tiles, if it can be played.
+    fn typed(&self) -> Option<BString> {
+        // a q on its own is waiting for its u
+        let typed = self.input.strip_suffix('q').unwrap_or(&self.input);
+        typed.parse().ok()
+    }
+
+    fn submit(&mut self, game: &Game) {
+        let word = std::mem::take(&mut self.input);
+        self.round.words.push(word.clone());
+        self.recorder.word(&word);
+        let verdict = self.checker.check(&word);
+        let score = game.scoring.score(&word);
+        if verdict == Verdict::Correct {
+            self.found.push((word.clone(), score));
+        }
+        if !self.feedback {
+            self.message = None;
+            return;
+        }
+        self.message = Some(match verdict {
+            Verdict::Correct => Line::from(format!("{}: {} points", word, score)).green(),
+            Verdict::NotPresent => {
+                Line::from(format!("{}: repeated or not on the board", word)).yellow()
+            }
+            Verdict::TooShort => Line::from(format!("{}: too short", word)).yellow(),
+            Verdict::NotWord => Line::from(format!("{}: not in the dictionary", word)).red(),
+            Verdict::NotBword => Line::from(format!("{}: not possible in Boggle", word)).red(),
+            Verdict::Blocked => Line::from(format!("{}: not allowed in this game", word)).red(),
+        });
+    }
+
+    /// Gives a bigger hint each time one is asked for in a row.
+    fn hint(&mut self, rng: &mut impl rand::Rng) {
+        let found: Dict = self
+            .round
+            .words
+            .iter()
+            .flat_map(|w| w.parse::<BString>())
+            .collect();
+        let hints = Hints::new(self.board, &self.shown, &found);
+        let hint = match self.hints_asked {
+            0 => Some(hints.counts()),
+            1 => hints.first_letters(2, rng),
+            _ => hints.starting_tile(rng),
+        };
+        let text = match hint {
+            Some(hint) => hint.to_string(),
+            None => String::from("No words left to find"),
+        };
+        self.hints_asked += 1;
+        self.message = Some(Line::from(text).cyan());
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        let [top, middle, bottom] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Min(14),
+            Constraint::Length(4),
+        ])
+        .areas(frame.area());
+
+        let time = match self.deadline {
+            Some(deadline) => format_time(deadline.saturating_duration_since(Instant::now())),
+            None => String::from("untimed"),
+        };
+        // without feedback, the words are only scored once the round is over
+        let (status, title, words): (_, _, Vec<ListItem>) = if self.feedback {
+            let score: u32 = self.found.iter().map(|(_, score)| score).sum();
+            let words = self
+                .found
+                .iter()
+                .rev()
+                .map(|(word, score)| ListItem::new(format!("{:16}{:>3}", word, score)))
+                .collect();
+            (format!("Score: {}", score), " Found words ", words)
+        } else {
+            let words = self
+                .round
+                .words
+                .iter()
+                .rev()
+                .map(|word| ListItem::new(word.as_str()))
+                .collect();
+            (
+                format!("Words: {}", self.round.words.len()),
+                " Words entered ",
+                words,
+            )
+        };
+        frame.render_widget(
+            Line::from(format!(" {}    Time: {}", status, time)).bold(),
+            top,
+        );
+
+        let [board_area, words_area] =
+            Layout::horizontal([Constraint::Length(32), Constraint::Min(20)]).areas(middle);
+        draw_board(frame, board_area, &self.board, &self.matcher);
+        frame.render_widget(
+            List::new(words).block(Block::bordered().title(title)),
+            words_area,
+        );
+
+        let input_style = if self.typed().is_none() || !self.matcher.is_on_board() {
+            Style::new().red()
+        } else if !self.matcher.extends() {
+            Style::new().yellow()
+        } else {
+            Style::new()
+        };
+        let lines = vec![
+            Line::from(vec![
+                Span::raw("> "),
+                Span::styled(self.input.as_str(), input_style),
+            ]),
+            self.message.clone().unwrap_or_default(),
+        ];
+        frame.render_widget(
+            Paragraph::new(lines)
+                .block(Block::bordered().title(" Enter to submit, ? for a hint, Esc to finish ")),
+            bottom,
+        );
+    }
+}
+
+/// Draws the board, highlighting the tiles that could spell out what has been typed.
+fn draw_board(frame: &mut Frame, area: Rect, board: &Board, matcher: &PathMatcher) {
+    let positions = matcher.positions();
+    let mut ends = [[false; 4]; 4];
+    for &(r, c) in matcher.paths().iter().flat_map(|path| path.last()) {
+        ends[r][c] = true;
+    }
+    let rows = Layout::vertical([Constraint::Length(3); 4]).split(area);
+    for (r, row_area) in rows.iter().enumerate() {
+        let cells = Layout::horizontal([Constraint::Length(7); 4]).split(*row_area);
+        for (c, cell) in cells.iter().enumerate() {
+            let style = if ends[r][c] {
+                Style::new()
+                    .black()
+                    .on_yellow()
+                    .add_modifier(Modifier::BOLD)
+            } else if positions[r][c] {
+                Style::new().black().on_green().add_modifier(Modifier::BOLD)
+            } else {
+                Style::new()
+            };
+            frame.render_widget(
+                Paragraph::new(board[r][c].to_string())
+                    .alignment(Alignment::Center)
+                    .style(style)
+                    .block(Block::bordered()),
+                *cell,
+            );
+        }
+    }
+}
+
+/// The results of a round, with a tab for each kind of word.
+struct ResultsScreen {
+    score: u32,
+    max_score: u32,
+    tabs: [Vec<String>; 3],
+    tab: usize,
+    scroll: usize,
+    /// A word being typed after a `+`, to add to the dictionary.
+    challenge: Option<String>,
+    /// What happened to the last word added.
+    message: Option<Line<'static>>,
+}
+
+impl ResultsScreen {
+    fn new(game: &Game, result: &RoundResult) -> Self {
+        let found = result
+            .correct
+            .iter()
+            .map(|word| format!("{:16}{:>3}", word, game.scoring.score(word)))
+            .collect();
+        let mut missed: Vec<String> = result.missed.iter().map(|w| w.to_string()).collect();
+        missed.sort_by_key(|w| std::cmp::Reverse(w.len()));
+        let missed = missed
+            .iter()
+            .map(|word| {
+                let line = format!("{:16}{:>3}", word, game.scoring.score(word));
+                match definition(&game.defs, word) {
+                    Some(def) => format!("{}  {}", line, def),
+                    None => line,
+                }
+            })
+            .collect();
+        let reasons = [
+            (&result.not_present, "repeated or not on the board"),
+            (&result.too_short, "too short"),
+            (&result.not_word, "not in the dictionary"),
+            (&result.not_bword, "not possible in Boggle"),
+            (&result.blocked, "not allowed in this game"),
+        ];
+        let rejected = reasons
+            .iter()
+            .flat_map(|(words, reason)| words.iter().map(move |w| format!("{:16}{}", w, reason)))
+            .collect();
+        ResultsScreen {
+            score: result.score_with(game.scoring),
+            max_score: result.max_score_with(game.scoring),
+            tabs: [found, missed, rejected],
+            tab: 0,
+            scroll: 0,
+            challenge: None,
+            message: None,
+        }
+    }
+
+    /// Shows the results, returning `false` if the player quits instead of playing again.
+    fn show(
+        &mut self,
+        terminal: &mut DefaultTerminal,
+        game: &Game,
+        words: &mut Cow<Dict>,
+        round: &Round,
+    ) -> Result<bool, Error> {
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+            let key = match event::read()? {
+                Event::Key(key) if key.kind == KeyEventKind::Press => key,
+                _ => continue,
+            };
+            if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+                return Ok(false);
+            }
+            if let Some(challenge) = &mut self.challenge {
+                match key.code {
+                    KeyCode::Char(c) if c.is_ascii_alphabetic() => {
+                        challenge.push(c.to_ascii_lowercase())
+                    }
+                    KeyCode::Backspace => {
+                        challenge.pop();
+                    }
+                    KeyCode::Enter => {
+                        let word = std::mem::take(challenge);
+                        self.challenge = None;
+                        self.add(game, words, round, &word)?;
+                    }
+                    KeyCode::Esc => self.challenge = None,
+                    _ => {}
+                }
+                continue;
+            }
+            match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(false),
+                KeyCode::Char('+') => self.challenge = Some(String::new()),
+                KeyCode::Enter | KeyCode::Char('n') => return Ok(true),
+                KeyCode::Tab | KeyCode::Right => self.select((self.tab + 1) % TABS.len()),
+                KeyCode::BackTab | KeyCode::Left => {
+                    self.select((self.tab + TABS.len() - 1) % TABS.len())
+                }
+                KeyCode::Down => {
+                    self.scroll = (self.scroll + 1).min(self.tabs[self.tab].len().saturating_sub(1))
+                }
+                KeyCode::Up => self.scroll = self.scroll.saturating_sub(1),
+                _ => {}
+            }
+        }
+    }
+
+    fn select(&mut self, tab: usize) {
+        self.tab = tab;
+        self.scroll = 0;
+    }
+
+    /// Adds a challenged word to the dictionary, scoring the round again if the word was played.
+    fn add(
+        &mut self,
+        game: &Game,
+        words: &mut Cow<Dict>,
+        round: &Round,
+        word: &str,
+    ) -> Result<(), Error> {
+        let message = match accept(game, words, word)? {
+            Some(message) => message,
+            None => {
+                let message = format!("{} can't be added to the dictionary", word);
+                self.message = Some(Line::from(message).red());
+                return Ok(());
+            }
+        };
+        if played(round, word) {
+            let result = round.result_filtered(&game.filter(words));
+            let tab = self.tab;
+            *self = ResultsScreen::new(game, &result);
+            self.tab = tab;
+        }
+        self.message = Some(Line::from(message).green());
+        Ok(())
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        let [top, tabs, body, status, bottom] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Length(1),
+            Constraint::Min(3),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+        frame.render_widget(
+            Line::from(format!(" Score: {} out of {}", self.score, self.max_score)).bold(),
+            top,
+        );
+        let titles = TABS
+            .iter()
+            .zip(self.tabs.iter())
+            .map(|(title, words)| format!("{} ({})", title, words.len()));
+        frame.render_widget(
+            Tabs::new(titles)
+                .select(self.tab)
+                .highlight_style(Style::new().yellow().bold()),
+            tabs,
+        );
+        let items: Vec<ListItem> = self.tabs[self.tab]
+            .iter()
+            .skip(self.scroll)
+            .map(|line| ListItem::new(line.as_str()))
+            .collect();
+        frame.render_widget(List::new(items).block(Block::bordered()), body);
+        let status_line = match &self.challenge {
+            Some(challenge) => Line::from(format!(" Add to the dictionary: +{}", challenge)),
+            None => self.message.clone().unwrap_or_default(),
+        };
+        frame.render_widget(status_line, status);
+        let help = match self.challenge {
+            Some(_) => " Enter to add the word, Esc to cancel",
+            None => {
+                " Tab to switch, arrows to scroll, + to add a word, Enter for another round, q to quit"
+            }
+        };
+        frame.render_widget(Line::from(help).italic(), bottom);
+    }
+}
@@ -0,0 +1,256 @@
+use boggle::filter::*;
+use boggle::io::*;
+use boggle::protocol::*;
+use boggle::room::Room;
+use boggle::*;
+use clap::builder::{PossibleValuesParser, RangedU64ValueParser, TypedValueParser};
+use clap::Parser;
+use std::collections::HashMap;
+use std::io::prelude::*;
+use std::io::{self, BufReader};
+use std::net::{Ipv4Addr, Shutdown, SocketAddr, TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::process;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Host Boggle games for players on other machines.
+///
+/// Players join a room by name, and everyone in a room plays the same board against the same
+/// timer. Words found by more than one player in a room are cancelled. The protocol is one JSON
+/// message per line; see `boggle::protocol`.
+#[derive(Debug, Parser)]
+#[command(name = "boggle-server", version)]
+struct Options {
+    /// Address to listen on
+    #[arg(long, default_value_t = SocketAddr::from((Ipv4Addr::LOCALHOST, DEFAULT_PORT)))]
+    bind: SocketAddr,
+    /// Word list to play with, one word per line. Give more than once to combine several
+    #[arg(short, long = "dict", value_name = "PATH", required = true)]
+    dicts: Vec<PathBuf>,
+    /// Words that score, but are never shown as missed words
+    #[arg(long, value_name = "PATH")]
+    blocklist: Option<PathBuf>,
+    /// Dice set file to roll [default: the classic dice]
+    #[arg(long, value_name = "PATH")]
+    dice: Option<PathBuf>,
+    /// Length of a round in seconds
+    #[arg(long, value_name = "SECS", default_value_t = 180)]
+    timer: u64,
+    /// How words score
+    #[arg(
+        long,
+        value_name = "PRESET",
+        default_value_t = Scoring::Classic,
+        value_parser = PossibleValuesParser::new(Scoring::ALL.map(Scoring::name))
+            .map(|s| s.parse::<Scoring>().unwrap()),
+    )]
+    scoring: Scoring,
+    /// Shortest word that scores, counting Qu as two letters. Word lists never hold words shorter
+    /// than 3 letters, so it can't be less
+    #[arg(
+        long,
+        value_name = "LETTERS",
+        default_value_t = 3,
+        value_parser = RangedU64ValueParser::<usize>::new().range(3..),
+    )]
+    min_len: usize,
+}
+
+fn main() {
+    if let Err(err) = run() {
+        eprintln!("error: {}", err);
+        process::exit(1);
+    }
+}
+
+fn run() -> Result<(), Error> {
+    let options = Options::parse();
+    let mut dict = Dict::new();
+    for path in options.dicts.iter() {
+        dict |= &read_dict(path)?;
+    }
+    let block = match &options.blocklist {
+        Some(path) => read_dict(path)?,
+        None => Dict::new(),
+    };
+    let dice = match &options.dice {
+        Some(path) => read_dice(path)?,
+        None => parse_dice_set(include_str!("../../dice.txt"))?.dice,
+    };
+    // the dictionaries are shared by every round for as long as the server runs
+    let dict: &'static Dict = Box::leak(Box::new(dict));
+    let block: &'static Dict = Box::leak(Box::new(block));
+    let mut filter = FilteredDict::new(dict, block);
+    filter.min_len = options.min_len;
+    let server = Arc::new(Server {
+        dict: Box::leak(Box::new(filter)),
+        dice,
+        timer: Duration::from_secs(options.timer),
+        scoring: options.scoring,
+        rooms: Mutex::new(HashMap::new()),
+        next_round: AtomicU64::new(0),
+    });
+
+    let listener = TcpListener::bind(options.bind)?;
+    eprintln!("Listening on {}", listener.local_addr()?);
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(err) => {
+                eprintln!("connection failed: {}", err);
+                continue;
+            }
+        };
+        let server = Arc::clone(&server);
+        thread::spawn(move || {
+            let peer = stream.peer_addr().ok();
+            if let Err(err) = server.handle(stream) {
+                eprintln!("{:?}: {}", peer, err);
+            }
+        });
+    }
+    Ok(())
+}
+
+struct Server {
+    dict: &'static FilteredDict<'static>,
+    dice: Dice,
+    timer: Duration,
+    scoring: Scoring,
+    rooms: Mutex<HashMap<String, Room<'static>>>,
+    next_round: AtomicU64,
+}
+
+/// The longest line a client may send, in bytes.
+const MAX_LINE: u64 = 4096;
+
+fn send(stream: &mut TcpStream, message: &ServerMessage) -> io::Result<()> {
+    let mut line = serde_json::to_string(message)?;
+    line.push('\n');
+    stream.write_all(line.as_bytes())
+}
+
+/// Writes the messages for one player as they are queued, until the player leaves. If the
+/// connection fails, it is shut down so that the player's reader notices and they leave.
+fn spawn_writer(mut stream: TcpStream) -> Sender<ServerMessage> {
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        for message in rx {
+            if send(&mut stream, &message).is_err() {
+                let _ = stream.shutdown(Shutdown::Both);
+                return;
+            }
+        }
+    });
+    tx
+}
+
+/// Reads a line without its line ending, or `None` at the end of the stream. Lines longer than
+/// [`MAX_LINE`] are an error, so that a client can't make the server buffer without limit.
+fn read_line(reader: &mut BufReader<TcpStream>) -> io::Result<Option<String>> {
+    let mut line = String::new();
+    let read = reader.by_ref().take(MAX_LINE + 1).read_line(&mut line)?;
+    if read == 0 {
+        return Ok(None);
+    }
+    if read as u64 > MAX_LINE && !line.ends_with('\n') {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "line too long"));
+    }
+    let len = line.trim_end_matches(['\r', '\n']).len();
+    line.truncate(len);
+    Ok(Some(line))
+}
+
+impl Server {
+    /// Talks to one player until they disconnect.
+    fn handle(self: &Arc<Self>, stream: TcpStream) -> Result<(), Error> {
+        let out = spawn_writer(stream.try_clone()?);
+        let mut reader = BufReader::new(stream);
+        let (room, name) = loop {
+            let line = match read_line(&mut reader)? {
+                Some(line) => line,
+                None => return Ok(()),
+            };
+            let message = match serde_json::from_str(&line) {
+                Ok(ClientMessage::Join { room, name }) if !name.is_empty() => {
+                    if self.join(&room, &name, &out) {
+                        break (room, name);
+                    }
+                    format!("{} is already in {}", name, room)
+                }
+                _ => String::from("join a room first"),
+            };
+            let _ = out.send(ServerMessage::Error { message });
+        };
+        let result = self.play(&room, &name, &mut reader);
+        self.leave(&room, &name);
+        result
+    }
+
+    /// Adds a player to a room, returning `false` if the name is taken.
+    fn join(&self, room_name: &str, name: &str, out: &Sender<ServerMessage>) -> bool {
+        let mut rooms = self.rooms.lock().unwrap();
+        rooms
+            .entry(room_name.to_string())
+            .or_insert_with(|| Room::new(room_name, self.dict, self.scoring))
+            .join(name, out.clone(), Instant::now())
+    }
+
+    fn leave(&self, room_name: &str, name: &str) {
+        let mut rooms = self.rooms.lock().unwrap();
+        if let Some(room) = rooms.get_mut(room_name) {
+            room.leave(name);
+            if room.is_empty() {
+                rooms.remove(room_name);
+            }
+        }
+    }
+
+    fn play(
+        self: &Arc<Self>,
+        room_name: &str,
+        name: &str,
+        reader: &mut BufReader<TcpStream>,
+    ) -> Result<(), Error> {
+        while let Some(line) = read_line(reader)? {
+            let message = serde_json::from_str(&line);
+            let mut rooms = self.rooms.lock().unwrap();
+            let room = match rooms.get_mut(room_name) {
+                Some(room) => room,
+                None => return Ok(()),
+            };
+            match message {
+                Err(err) => room.error(name, &err.to_string()),
+                Ok(ClientMessage::Join { .. }) => {
+                    room.error(name, &format!("already in {}", room_name))
+                }
+                Ok(ClientMessage::Start) => self.start(room_name, room),
+                Ok(ClientMessage::Word { word }) => room.word(name, word, Instant::now()),
+            }
+        }
+        Ok(())
+    }
+
+    fn start(self: &Arc<Self>, room_name: &str, room: &mut Room) {
+        let board = roll(&self.dice, &mut rand::thread_rng());
+        let id = self.next_round.fetch_add(1, Ordering::Relaxed);
+        let now = Instant::now();
+        if !room.start(id, board, now + self.timer, now) {
+            return;
+        }
+        let (server, room_name) = (Arc::clone(self), room_name.to_string());
+        thread::spawn(move || {
+            thread::sleep(server.timer);
+            let mut rooms = server.rooms.lock().unwrap();
+            if let Some(room) = rooms.get_mut(&room_name) {
+                if room.round_id() == Some(id) {
+                    room.finish_round();
+                }
+            }
+        });
+    }
+}
@@ -0,0 +1,95 @@
+use crate::*;
+
+/// What to do with a blocked word in a particular context.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockPolicy {
+    Allow,
+    Block,
+}
+
+/// A dictionary with a blocklist applied on top of it.
+#[derive(Debug, Clone, Copy)]
+pub struct FilteredDict<'a> {
+    allow: &'a Dict,
+    block: &'a Dict,
+    /// Whether blocked words score when played.
+    pub play: BlockPolicy,
+    /// Whether blocked words are shown as hints or missed words.
+    pub hint: BlockPolicy,
+    /// The shortest word that scores, counting `Qu` as two letters.
+    pub min_len: usize,
+}
+
+impl<'a> FilteredDict<'a> {
+    pub fn new(allow: &'a Dict, block: &'a Dict) -> Self {
+        FilteredDict {
+            allow,
+            block,
+            play: BlockPolicy::Allow,
+            hint: BlockPolicy::Block,
+            min_len: 3,
+        }
+    }
+
+    pub fn allow(&self) -> &'a Dict {
+        self.allow
+    }
+
+    pub fn block(&self) -> &'a Dict {
+        self.block
+    }
+
+    fn long_enough(&self, word: &BStr) -> bool {
+        word.to_string().len() >= self.min_len
+    }
+
+    fn permits(&self, policy: BlockPolicy, word: &BStr) -> bool {
+        self.allow.contains(word)
+            && self.long_enough(word)
+            && (policy == BlockPolicy::Allow || !self.block.contains(word))
+    }
+
+    /// Whether `word` scores when played.
+    pub fn accepts(&self, word: &BStr) -> bool {
+        self.permits(self.play, word)
+    }
+
+    /// Whether `word` is a dictionary word that doesn't score only because it is blocked.
+    pub fn blocks(&self, word: &BStr) -> bool {
+        self.play == BlockPolicy::Block
+            && self.allow.contains(word)
+            && self.long_enough(word)
+            && self.block.contains(word)
+    }
+
+    /// Whether `word` may be shown to the player.
+    pub fn shows(&self, word: &BStr) -> bool {
+        self.permits(self.hint, word)
+    }
+
+    fn filter(&self, policy: BlockPolicy, mut words: Dict) -> Dict {
+        if policy == BlockPolicy::Block {
+            words -= self.block;
+        }
+        let short: Vec<BString> = words.iter().filter(|w| !self.long_enough(w)).collect();
+        for word in short.iter() {
+            words.remove(word);
+        }
+        words
+    }
+
+    /// Returns the words on `board` that score when played.
+    pub fn playable_words(&self, board: &Board) -> Dict {
+        self.filter(self.play, board.words_trie(self.allow))
+    }
+
+    /// Returns the words on `board` that may be shown to the player.
+    pub fn shown_words(&self, board: &Board) -> Dict {
+        self.filter(self.hint, board.words_trie(self.allow))
+    }
+
+    /// Removes the words that may not be shown to the player from `words`.
+    pub fn hide(&self, words: Dict) -> Dict {
+        self.filter(self.hint, words)
+    }
+}
@@ -0,0 +1,86 @@
+use crate::*;
+use std::collections::BTreeMap;
+
+/// A hint about a word the player has not found yet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Hint {
+    /// The number of unfound words of each length in letters, counting Qu as two.
+    Counts(BTreeMap<usize, usize>),
+    /// The first tiles of an unfound word, and its length in letters, counting Qu as two.
+    FirstLetters(BString, usize),
+    /// The position of the first tile of an unfound word, as `(row, column)`.
+    StartingTile(usize, usize),
+}
+
+/// Hints from the words on the board that the player has not found yet.
+#[derive(Debug, Clone)]
+pub struct Hints {
+    board: Board,
+    remaining: Dict,
+}
+
+impl Hints {
+    /// `words` are the words on `board` that may be hinted, such as those from
+    /// [`FilteredDict::shown_words`](crate::filter::FilteredDict::shown_words).
+    pub fn new(board: Board, words: &Dict, found: &Dict) -> Self {
+        Hints {
+            board,
+            remaining: words - found,
+        }
+    }
+
+    pub fn remaining(&self) -> &Dict {
+        &self.remaining
+    }
+
+    /// Counts the unfound words by length, counting Qu as two letters as scoring does.
+    pub fn counts(&self) -> Hint {
+        let mut counts = BTreeMap::new();
+        self.remaining
+            .traverse(|w| *counts.entry(w.to_string().len()).or_insert(0) += 1);
+        Hint::Counts(counts)
+    }
+
+    /// Reveals up to `n` tiles of a randomly chosen unfound word.
+    pub fn first_letters<R: Rng + ?Sized>(&self, n: usize, rng: &mut R) -> Option<Hint> {
+        let word = self.remaining.iter().choose(rng)?;
+        let len = word.to_string().len();
+        let mut letters = word.to_vec();
+        letters.truncate(n);
+        Some(Hint::FirstLetters(
+            BStr::from_slice(&letters).to_owned(),
+            len,
+        ))
+    }
+
+    /// Reveals where a randomly chosen unfound word starts.
+    pub fn starting_tile<R: Rng + ?Sized>(&self, rng: &mut R) -> Option<Hint> {
+        let word = self.remaining.iter().choose(rng)?;
+        let path = self.board.find_path(&word)?;
+        let (row, col) = path[0];
+        Some(Hint::StartingTile(row, col))
+    }
+}
+
+impl fmt::Display for Hint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Hint::Counts(counts) => {
+                if counts.is_empty() {
+                    return write!(f, "No words left to find");
+                }
+                let counts: Vec<String> = counts
+                    .iter()
+                    .map(|(len, n)| format!("{} of length {}", n, len))
+                    .collect();
+                write!(f, "Words left: {}", counts.join(", "))
+            }
+            Hint::FirstLetters(letters, len) => {
+                write!(f, "A {}-letter word starts with {}", len, letters)
+            }
+            Hint::StartingTile(row, col) => {
+                write!(f, "A word starts at row {}, column {}", row + 1, col + 1)
+            }
+        }
+    }
+}
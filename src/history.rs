@@ -0,0 +1,191 @@
+//! Records of the rounds played, so that games can be looked back on and players can follow how
+//! they are getting on.
+
+use crate::round::RoundResult;
+use crate::*;
+use std::cmp::Reverse;
+
+/// One round, as saved in the history log.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GameRecord {
+    /// When the round started, in seconds since the Unix epoch.
+    pub time: u64,
+    pub board: Board,
+    /// The seed the boards were rolled with, if one was chosen.
+    #[serde(default)]
+    pub seed: Option<u64>,
+    /// The name of the dice set, if known.
+    #[serde(default)]
+    pub dice: Option<String>,
+    /// The [`Dict::fingerprint`] of the dictionary, in hex, if known.
+    #[serde(default)]
+    pub dict: Option<String>,
+    pub scoring: Scoring,
+    pub min_len: usize,
+    pub players: Vec<PlayerRecord>,
+}
+
+/// How one player did in a round.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlayerRecord {
+    pub name: String,
+    /// The words in the order they were entered. This is empty for players on other machines,
+    /// whose words are only known once the round is over.
+    #[serde(default)]
+    pub words: Vec<WordRecord>,
+    pub result: RoundResult,
+    pub score: u32,
+}
+
+/// A word as it was entered.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WordRecord {
+    pub word: String,
+    /// How long after the start of the round it was entered, in milliseconds.
+    pub millis: u64,
+}
+
+impl GameRecord {
+    pub fn player(&self, name: &str) -> Option<&PlayerRecord> {
+        self.players.iter().find(|player| player.name == name)
+    }
+}
+
+impl PlayerRecord {
+    /// The longest word the player found, whether it scored or was cancelled, counting Qu as two
+    /// letters. The first one wins ties.
+    pub fn longest_word(&self) -> Option<&str> {
+        self.result
+            .correct
+            .iter()
+            .chain(self.result.cancelled.iter())
+            .reduce(|longest, word| {
+                if word.len() > longest.len() {
+                    word
+                } else {
+                    longest
+                }
+            })
+            .map(String::as_str)
+    }
+}
+
+/// Formats a dictionary fingerprint the way it is saved in [`GameRecord::dict`].
+pub fn dict_id<V>(dict: &Dict<V>) -> String {
+    format!("{:016x}", dict.fingerprint())
+}
+
+/// How a player has done over all the rounds they played.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlayerStats {
+    pub name: String,
+    pub rounds: usize,
+    pub average_score: f64,
+    /// The share of the points available on the boards that the player scored, from 0 to 1.
+    pub points_found: f64,
+    /// The longest word the player found, as in [`PlayerRecord::longest_word`]. The first found
+    /// wins ties.
+    pub longest_word: Option<String>,
+    /// The words the player missed, those that cost the most points over all rounds first.
+    pub missed: Vec<MissedWord>,
+    /// The share of the available points found in each round, oldest first.
+    pub progress: Vec<f64>,
+    /// How much the share of points found changes from one round to the next: the slope of the
+    /// line of best fit through `progress`. It is positive when the player is improving.
+    pub improvement: f64,
+}
+
+/// A word that was on the board but not found.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MissedWord {
+    pub word: String,
+    /// The number of rounds it was missed in.
+    pub times: u32,
+    /// The points it would have scored in those rounds.
+    pub points: u32,
+}
+
+impl PlayerStats {
+    /// Works out how the player called `name` did in `games`, which should be oldest first.
+    /// Rounds they didn't play in are skipped.
+    pub fn new<'a>(games: impl IntoIterator<Item = &'a GameRecord>, name: &str) -> Self {
+        let mut stats = PlayerStats {
+            name: name.to_string(),
+            ..PlayerStats::default()
+        };
+        let (mut score, mut available) = (0u64, 0u64);
+        let mut missed: Dict<(u32, u32)> = Dict::default();
+        for game in games {
+            let player = match game.player(name) {
+                Some(player) => player,
+                None => continue,
+            };
+            let result = &player.result;
+            let max_score = result.max_score_with(game.scoring);
+            stats.rounds += 1;
+            score += u64::from(player.score);
+            available += u64::from(max_score);
+            stats.progress.push(match max_score {
+                0 => 0.0,
+                max_score => f64::from(player.score) / f64::from(max_score),
+            });
+            if let Some(word) = player.longest_word() {
+                if stats
+                    .longest_word
+                    .as_ref()
+                    .is_none_or(|longest| word.len() > longest.len())
+                {
+                    stats.longest_word = Some(word.to_string());
+                }
+            }
+            result.missed.traverse(|word| {
+                let points = game.scoring.score(&word.to_string());
+                match missed.get_mut(word) {
+                    Some((times, total)) => {
+                        *times += 1;
+                        *total += points;
+                    }
+                    None => {
+                        missed.insert_value(word, (1, points));
+                    }
+                }
+            });
+        }
+        if stats.rounds > 0 {
+            stats.average_score = score as f64 / stats.rounds as f64;
+        }
+        if available > 0 {
+            stats.points_found = score as f64 / available as f64;
+        }
+        missed.traverse_entries(|word, &(times, points)| {
+            stats.missed.push(MissedWord {
+                word: word.to_string(),
+                times,
+                points,
+            })
+        });
+        // sorting is stable, so words costing the same stay in alphabetical order
+        stats
+            .missed
+            .sort_by_key(|word| Reverse((word.points, word.times)));
+        stats.improvement = slope(&stats.progress);
+        stats
+    }
+}
+
+/// The slope of the least-squares line through `ys`, taking the x of each to be its index.
+fn slope(ys: &[f64]) -> f64 {
+    let n = ys.len() as f64;
+    if ys.len() < 2 {
+        return 0.0;
+    }
+    let mean_x = (n - 1.0) / 2.0;
+    let mean_y = ys.iter().sum::<f64>() / n;
+    let (mut cov, mut var) = (0.0, 0.0);
+    for (x, y) in ys.iter().enumerate() {
+        let dx = x as f64 - mean_x;
+        cov += dx * (y - mean_y);
+        var += dx * dx;
+    }
+    cov / var
+}
@@ -1,6 +1,9 @@
 #[cfg(feature = "gio1")]
 pub mod gio;
 
+#[cfg(feature = "json")]
+use crate::history::GameRecord;
+use crate::layered::LayeredDict;
 use crate::*;
 use std::convert::TryFrom;
 use std::error;
@@ -44,19 +47,161 @@ impl From<io::Error> for Error {
 }
 
 pub fn read_dice<P: AsRef<Path>>(path: P) -> Result<Dice, Error> {
-    let f = File::open(path)?;
-    let buf_reader = io::BufReader::new(f);
+    read_dice_set(path).map(|set| set.dice)
+}
+
+pub fn read_dice_set<P: AsRef<Path>>(path: P) -> Result<DiceSet, Error> {
+    let mut f = File::open(path)?;
+    let mut contents = String::new();
+    f.read_to_string(&mut contents)?;
+    parse_dice_set(&contents)
+}
+
+/// Parses a dice file.
+///
+/// Everything after a `#` is a comment and blank lines are ignored. The file may start with
+/// `key: value` header lines giving the `name` of the set and the board `size` (only `4x4` is
+/// supported); a `\`, `#` or line break in the name is written as `\\`, `\#` or `\n`. Each
+/// remaining line is one die, either as six faces separated by whitespace (`M U Qu H I N`) or, as
+/// in older files, all six faces run together (`MUQuHIN`).
+pub fn parse_dice_set(s: &str) -> Result<DiceSet, Error> {
+    let mut name = None;
     let mut dice_vec = Vec::new();
-    for line_res in buf_reader.lines() {
-        let parsed = line_res?
-            .parse::<BString>()
-            .map_err(|_| Error::SimpleMessage("invalid character"))?;
-        let die_vec = parsed.to_vec();
-        let die = <[BChar; 6]>::try_from(die_vec)
-            .map_err(|_vec| Error::SimpleMessage("wrong number of faces"))?;
+    for (i, line) in s.lines().enumerate() {
+        let line_no = i + 1;
+        let line = strip_comment(line).trim();
+        if line.is_empty() {
+            continue;
+        }
+        if let Some((key, value)) = line.split_once(':') {
+            if !dice_vec.is_empty() {
+                return Err(Error::StringMessage(format!(
+                    "line {}: header after the first die",
+                    line_no
+                )));
+            }
+            let value = value.trim();
+            match key.trim() {
+                "name" => {
+                    name = Some(unescape(value).ok_or_else(|| {
+                        Error::StringMessage(format!("line {}: invalid escape", line_no))
+                    })?)
+                }
+                "size" => {
+                    if value != "4x4" {
+                        return Err(Error::StringMessage(format!(
+                            "line {}: unsupported board size: {}",
+                            line_no, value
+                        )));
+                    }
+                }
+                key => {
+                    return Err(Error::StringMessage(format!(
+                        "line {}: unknown header: {}",
+                        line_no, key
+                    )))
+                }
+            }
+            continue;
+        }
+        let faces = if line.contains(char::is_whitespace) {
+            line.split_whitespace()
+                .map(|face| match face.parse::<BString>().map(BString::to_vec) {
+                    Ok(v) if v.len() == 1 => Ok(v[0]),
+                    _ => Err(Error::StringMessage(format!(
+                        "line {}: invalid face: {}",
+                        line_no, face
+                    ))),
+                })
+                .collect::<Result<Vec<BChar>, Error>>()?
+        } else {
+            line.parse::<BString>()
+                .map_err(|_| Error::StringMessage(format!("line {}: invalid character", line_no)))?
+                .to_vec()
+        };
+        let die = <[BChar; 6]>::try_from(faces).map_err(|_vec| {
+            Error::StringMessage(format!("line {}: wrong number of faces", line_no))
+        })?;
         dice_vec.push(die);
     }
-    Dice::try_from(dice_vec).map_err(|_vec| Error::SimpleMessage("wrong number of dice"))
+    let dice =
+        Dice::try_from(dice_vec).map_err(|_vec| Error::SimpleMessage("wrong number of dice"))?;
+    Ok(DiceSet { name, dice })
+}
+
+/// Cuts a line off at the first `#` that isn't escaped with a backslash.
+fn strip_comment(line: &str) -> &str {
+    let mut escaped = false;
+    for (idx, c) in line.char_indices() {
+        match c {
+            '#' if !escaped => return &line[..idx],
+            '\\' => escaped = !escaped,
+            _ => escaped = false,
+        }
+    }
+    line
+}
+
+/// Escapes a header value so that it stays on one line and isn't cut short as a comment.
+pub(crate) fn escape_header(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '#' => out.push_str("\\#"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Escapes a field of a tab-separated line so that it stays in its field.
+fn escape_field(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '\t' => out.push_str("\\t"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Undoes [`escape_header`] or [`escape_field`], or returns `None` for an unknown escape.
+fn unescape(value: &str) -> Option<String> {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        out.push(match chars.next()? {
+            '\\' => '\\',
+            '#' => '#',
+            'n' => '\n',
+            'r' => '\r',
+            't' => '\t',
+            _ => return None,
+        });
+    }
+    Some(out)
+}
+
+pub fn write_dice<P: AsRef<Path>>(path: P, dice: &Dice) -> Result<(), Error> {
+    write_dice_set(path, &DiceSet::from(*dice))
+}
+
+pub fn write_dice_set<P: AsRef<Path>>(path: P, set: &DiceSet) -> Result<(), Error> {
+    let f = File::create(path)?;
+    let mut buf_writer = io::BufWriter::new(f);
+    write!(buf_writer, "{}", set)?;
+    buf_writer.flush().map_err(|e| e.into())
 }
 
 pub fn try_read_dict<P: AsRef<Path>>(path: P) -> Result<Dict, Error> {
@@ -95,8 +240,170 @@ pub fn read_dict<P: AsRef<Path>>(path: P) -> Result<Dict, Error> {
 pub fn write_dict<P: AsRef<Path>>(path: P, dict: &Dict) -> Result<(), Error> {
     let f = File::create(path)?;
     let mut buf_writer = io::BufWriter::new(f);
-    dict.try_traverse(|w| {
-        writeln!(buf_writer, "{}", w)
-    })?;
+    dict.try_traverse(|w| writeln!(buf_writer, "{}", w))?;
+    buf_writer.flush().map_err(|e| e.into())
+}
+
+fn read_dict_or_empty(path: &Path) -> Result<Dict, Error> {
+    match File::open(path) {
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Dict::new()),
+        _ => read_dict(path),
+    }
+}
+
+/// Reads the words added to and removed from `base`, as written by [`write_overlay`]. Files that
+/// don't exist are read as empty, since nothing has been changed yet.
+pub fn read_overlay<P: AsRef<Path>, Q: AsRef<Path>>(
+    base: &Dict,
+    added: P,
+    removed: Q,
+) -> Result<LayeredDict<'_>, Error> {
+    Ok(LayeredDict::with_overlay(
+        base,
+        read_dict_or_empty(added.as_ref())?,
+        read_dict_or_empty(removed.as_ref())?,
+    ))
+}
+
+/// Writes the words added and removed by a [`LayeredDict`], leaving its base alone.
+pub fn write_overlay<P: AsRef<Path>, Q: AsRef<Path>>(
+    dict: &LayeredDict,
+    added: P,
+    removed: Q,
+) -> Result<(), Error> {
+    let (added_words, removed_words) = dict.overlay();
+    write_dict(added, added_words)?;
+    write_dict(removed, removed_words)
+}
+
+/// Reads a word list in which each line is a word and its frequency, separated by a tab.
+///
+/// As with [`read_dict`], words that are too short or cannot be played are skipped.
+pub fn read_freq_dict<P: AsRef<Path>>(path: P) -> Result<Dict<u64>, Error> {
+    let f = File::open(path)?;
+    let buf_reader = io::BufReader::new(f);
+    let mut dict = Dict::default();
+    for line_res in buf_reader.lines() {
+        let line = line_res?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let (word, freq) = line
+            .split_once('\t')
+            .ok_or_else(|| Error::StringMessage(format!("missing frequency: {}", line)))?;
+        let freq = freq
+            .trim()
+            .parse::<u64>()
+            .map_err(|_| Error::StringMessage(format!("invalid frequency: {}", line)))?;
+        if word.len() < 3 {
+            continue;
+        }
+        if let Ok(parsed) = word.parse::<BString>() {
+            dict.insert_value(&parsed, freq);
+        }
+    }
+    Ok(dict)
+}
+
+pub fn write_freq_dict<P: AsRef<Path>>(path: P, dict: &Dict<u64>) -> Result<(), Error> {
+    let f = File::create(path)?;
+    let mut buf_writer = io::BufWriter::new(f);
+    dict.try_traverse_entries(|w, freq| writeln!(buf_writer, "{}\t{}", w, freq))?;
+    buf_writer.flush().map_err(|e| e.into())
+}
+
+/// Reads definitions from a file in which each line is a word and its definition, separated by a
+/// tab, with a tab, line break or backslash in a definition written as `\t`, `\n` or `\\`. A
+/// word appearing on several lines has its definitions joined with `"; "`.
+pub fn read_definitions<P: AsRef<Path>>(path: P) -> Result<Definitions, Error> {
+    let f = File::open(path)?;
+    let buf_reader = io::BufReader::new(f);
+    let mut defs = Definitions::default();
+    for line_res in buf_reader.lines() {
+        let line = line_res?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let (word, def) = line
+            .split_once('\t')
+            .ok_or_else(|| Error::StringMessage(format!("missing definition: {}", line)))?;
+        if let Ok(parsed) = word.trim().parse::<BString>() {
+            let def = def.trim();
+            // backslashes in files not written by `write_definitions` are taken as they are
+            let def = unescape(def).unwrap_or_else(|| String::from(def));
+            add_definition(&mut defs, &parsed, &def);
+        }
+    }
+    Ok(defs)
+}
+
+fn add_definition(defs: &mut Definitions, word: &BStr, def: &str) {
+    match defs.get_mut(word) {
+        Some(existing) => {
+            existing.push_str("; ");
+            existing.push_str(def);
+        }
+        None => {
+            defs.insert_value(word, String::from(def));
+        }
+    }
+}
+
+/// Reads definitions from a JSON object mapping each word to its definition.
+#[cfg(feature = "json")]
+pub fn read_definitions_json<P: AsRef<Path>>(path: P) -> Result<Definitions, Error> {
+    let f = File::open(path)?;
+    let buf_reader = io::BufReader::new(f);
+    let map: std::collections::BTreeMap<String, String> = serde_json::from_reader(buf_reader)
+        .map_err(|e| Error::StringMessage(format!("invalid definitions file: {}", e)))?;
+    let mut defs = Definitions::default();
+    for (word, def) in map {
+        if let Ok(parsed) = word.parse::<BString>() {
+            add_definition(&mut defs, &parsed, &def);
+        }
+    }
+    Ok(defs)
+}
+
+/// Reads the rounds saved by [`append_history`], oldest first. A log that doesn't exist yet has
+/// no rounds in it.
+#[cfg(feature = "json")]
+pub fn read_history<P: AsRef<Path>>(path: P) -> Result<Vec<GameRecord>, Error> {
+    let f = match File::open(path) {
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        f => f?,
+    };
+    let buf_reader = io::BufReader::new(f);
+    let mut games = Vec::new();
+    for (i, line_res) in buf_reader.lines().enumerate() {
+        let line = line_res?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let game = serde_json::from_str(&line).map_err(|e| {
+            Error::StringMessage(format!("invalid history on line {}: {}", i + 1, e))
+        })?;
+        games.push(game);
+    }
+    Ok(games)
+}
+
+/// Adds a round to the end of a history log, one JSON object per line, creating the log if
+/// needed.
+#[cfg(feature = "json")]
+pub fn append_history<P: AsRef<Path>>(path: P, game: &GameRecord) -> Result<(), Error> {
+    let mut line = serde_json::to_string(game).map_err(|e| Error::StringMessage(e.to_string()))?;
+    line.push('\n');
+    let mut f = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+    f.write_all(line.as_bytes()).map_err(|e| e.into())
+}
+
+pub fn write_definitions<P: AsRef<Path>>(path: P, defs: &Definitions) -> Result<(), Error> {
+    let f = File::create(path)?;
+    let mut buf_writer = io::BufWriter::new(f);
+    defs.try_traverse_entries(|w, def| writeln!(buf_writer, "{}\t{}", w, escape_field(def)))?;
     buf_writer.flush().map_err(|e| e.into())
 }
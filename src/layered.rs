@@ -0,0 +1,110 @@
+use crate::*;
+
+/// A read-only base dictionary with words added and removed on top of it.
+///
+/// Only the overlay changes, so a large shared word list can be kept as it is while each group of
+/// players keeps its own additions and removals, which are all that needs to be saved.
+#[derive(Debug, Clone)]
+pub struct LayeredDict<'a> {
+    base: &'a Dict,
+    added: Dict,
+    removed: Dict,
+}
+
+impl<'a> LayeredDict<'a> {
+    pub fn new(base: &'a Dict) -> Self {
+        LayeredDict {
+            base,
+            added: Dict::new(),
+            removed: Dict::new(),
+        }
+    }
+
+    /// Combines `base` with an overlay that was saved earlier, in which a word both added and
+    /// removed counts as added. Added words already in the base and removed words not in it make
+    /// no difference, but are kept so that they are saved again and still apply to other bases.
+    pub fn with_overlay(base: &'a Dict, added: Dict, mut removed: Dict) -> Self {
+        removed -= &added;
+        LayeredDict {
+            base,
+            added,
+            removed,
+        }
+    }
+
+    pub fn base(&self) -> &'a Dict {
+        self.base
+    }
+
+    /// Words that are in the dictionary but not in the base.
+    pub fn added(&self) -> Dict {
+        let mut added = self.added.clone();
+        added -= self.base;
+        added
+    }
+
+    /// Words in the base that are not in the dictionary.
+    pub fn removed(&self) -> Dict {
+        let mut removed = self.removed.clone();
+        removed &= self.base;
+        removed
+    }
+
+    /// The words added and removed as they are saved, including those that make no difference to
+    /// this base.
+    pub fn overlay(&self) -> (&Dict, &Dict) {
+        (&self.added, &self.removed)
+    }
+
+    pub fn contains(&self, word: &BStr) -> bool {
+        self.added.contains(word) || (self.base.contains(word) && !self.removed.contains(word))
+    }
+
+    /// Adds a word, returning whether it was not in the dictionary already.
+    pub fn insert(&mut self, word: &BStr) -> bool {
+        if self.contains(word) {
+            return false;
+        }
+        self.removed.remove(word);
+        if !self.base.contains(word) {
+            self.added.insert(word);
+        }
+        true
+    }
+
+    /// Removes a word, returning whether it was in the dictionary.
+    pub fn remove(&mut self, word: &BStr) -> bool {
+        if !self.contains(word) {
+            return false;
+        }
+        self.added.remove(word);
+        if self.base.contains(word) {
+            self.removed.insert(word);
+        }
+        true
+    }
+
+    pub fn len(&self) -> usize {
+        self.base.len() + self.added().len() - self.removed().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the words on `board`, as [`Board::words_trie`] would for the merged dictionary.
+    pub fn words_trie(&self, board: &Board) -> Dict {
+        let mut words = board.words_trie(self.base);
+        words -= &self.removed;
+        words |= &board.words_trie(&self.added);
+        words
+    }
+
+    /// Returns the base and the overlay merged into one dictionary.
+    pub fn merged(&self) -> Dict {
+        let mut words = self.base.clone();
+        words -= &self.removed;
+        words |= &self.added;
+        words
+    }
+}
@@ -1,26 +1,41 @@
+pub mod filter;
+pub mod hint;
+#[cfg(feature = "serde")]
+pub mod history;
 pub mod io;
+pub mod layered;
+pub mod matcher;
+#[cfg(feature = "serde")]
+pub mod protocol;
 #[cfg(feature = "serde")]
-mod serde;
+pub mod room;
+pub mod round;
+#[cfg(feature = "serde")]
+pub mod serde;
 #[cfg(test)]
 mod tests;
+pub mod trainer;
 
+#[cfg(feature = "serde")]
+use ::serde::{Deserialize, Serialize};
 use enum_map::{Enum, EnumMap};
 use rand::prelude::*;
+use std::borrow::Borrow;
 use std::error;
 use std::fmt;
-use std::iter::FromIterator;
-use std::mem;
 use std::hash;
+use std::iter::{FromIterator, FusedIterator};
+use std::mem;
 use std::ops::Deref;
-use std::borrow::Borrow;
-use std::ops::{Index, IndexMut};
+use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Sub, SubAssign};
+use std::ops::{Bound, Index, IndexMut, RangeBounds};
 use std::str::FromStr;
-#[cfg(feature = "serde")]
-use ::serde::{Serialize, Deserialize};
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Debug, PartialEq, Eq, Clone, Copy, Enum, Hash)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Enum, Hash, Default)]
+#[repr(u8)]
 pub enum BChar {
+    #[default]
     A,
     B,
     C,
@@ -50,12 +65,6 @@ pub enum BChar {
 }
 use BChar::*;
 
-impl Default for BChar {
-    fn default() -> Self {
-        A
-    }
-}
-
 impl fmt::Display for BChar {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.pad(match self {
@@ -180,6 +189,14 @@ impl BStr {
             .map(|(c, rem)| (c, BStr::from_slice(rem)))
     }
 
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
     pub fn from_slice(v: &[BChar]) -> &BStr {
         // SAFETY: BStr is a "newtype" of [BChar] with repr(transparent)
         unsafe { mem::transmute(v) }
@@ -254,39 +271,45 @@ impl ToOwned for BStr {
     }
 }
 
-#[derive(Default, PartialEq, Eq, Clone)]
+#[derive(PartialEq, Eq, Clone)]
 #[repr(transparent)]
-struct DictChildren(EnumMap<BChar, Option<Box<Dict>>>);
-
-impl DictChildren {
-    fn values(&self) -> enum_map::Values<Option<Box<Dict>>> {
-        self.0.values()
-    }
+struct DictChildren<V>(EnumMap<BChar, Option<Box<Dict<V>>>>);
 
-    fn values_mut(&mut self) -> enum_map::ValuesMut<Option<Box<Dict>>> {
+impl<V> DictChildren<V> {
+    fn values_mut(&mut self) -> enum_map::ValuesMut<'_, Option<Box<Dict<V>>>> {
         self.0.values_mut()
     }
 
-    fn iter(&self) -> enum_map::Iter<BChar, Option<Box<Dict>>> {
+    fn iter(&self) -> enum_map::Iter<'_, BChar, Option<Box<Dict<V>>>> {
         self.0.iter()
     }
+
+    fn iter_mut(&mut self) -> enum_map::IterMut<'_, BChar, Option<Box<Dict<V>>>> {
+        self.0.iter_mut()
+    }
 }
 
-impl Index<BChar> for DictChildren {
-    type Output = <EnumMap<BChar, Option<Box<Dict>>> as Index<BChar>>::Output;
+impl<V> Default for DictChildren<V> {
+    fn default() -> Self {
+        DictChildren(Default::default())
+    }
+}
+
+impl<V> Index<BChar> for DictChildren<V> {
+    type Output = Option<Box<Dict<V>>>;
 
     fn index(&self, index: BChar) -> &Self::Output {
         self.0.index(index)
     }
 }
 
-impl IndexMut<BChar> for DictChildren {
+impl<V> IndexMut<BChar> for DictChildren<V> {
     fn index_mut(&mut self, index: BChar) -> &mut Self::Output {
         self.0.index_mut(index)
     }
 }
 
-impl fmt::Debug for DictChildren {
+impl<V: fmt::Debug> fmt::Debug for DictChildren<V> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         f.debug_map()
             .entries(
@@ -297,70 +320,140 @@ impl fmt::Debug for DictChildren {
     }
 }
 
-#[derive(Debug, Default, PartialEq, Eq, Clone)]
-pub struct Dict {
-    val: bool,
-    children: DictChildren,
+/// A trie of words, each associated with a value of type `V`.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Dict<V = ()> {
+    val: Option<V>,
+    len: usize,
+    children: DictChildren<V>,
+}
+
+impl<V> Default for Dict<V> {
+    fn default() -> Self {
+        Dict {
+            val: None,
+            len: 0,
+            children: Default::default(),
+        }
+    }
 }
 
 impl Dict {
+    /// Creates an empty set of words. Use `Dict::default()` for other value types.
     pub fn new() -> Self {
         Default::default()
     }
 
-    pub fn insert(&mut self, word: &BStr) {
-        match word.split_first() {
-            None => {
-                self.val = true;
-            }
-            Some((&c, rem)) => {
-                self.children[c]
-                    .get_or_insert_with(Default::default)
-                    .insert(rem);
-            }
+    /// Adds `word`, returning whether it was not already present.
+    pub fn insert(&mut self, word: &BStr) -> bool {
+        self.insert_value(word, ()).is_none()
+    }
+
+    /// Removes and returns the first word in sorted order.
+    pub fn pop_first(&mut self) -> Option<BString> {
+        self.pop_first_entry().map(|(w, ())| w)
+    }
+
+    /// Removes and returns the last word in sorted order.
+    pub fn pop_last(&mut self) -> Option<BString> {
+        self.pop_last_entry().map(|(w, ())| w)
+    }
+}
+
+impl<V> Dict<V> {
+    /// Associates `value` with `word`, returning the value it replaced, if any.
+    pub fn insert_value(&mut self, word: &BStr, value: V) -> Option<V> {
+        let old = match word.split_first() {
+            None => self.val.replace(value),
+            Some((&c, rem)) => self.children[c]
+                .get_or_insert_with(Default::default)
+                .insert_value(rem, value),
+        };
+        if old.is_none() {
+            self.len += 1;
         }
+        old
     }
 
-    pub fn remove(&mut self, word: &BStr) {
-        match word.split_first() {
-            None => {
-                self.val = false;
-            }
+    /// Removes `word`, returning its value if it was present.
+    pub fn remove_value(&mut self, word: &BStr) -> Option<V> {
+        let removed = match word.split_first() {
+            None => self.val.take(),
             Some((&c, rem)) => match &mut self.children[c] {
-                None => {}
+                None => None,
                 Some(dict) => {
-                    dict.remove(rem);
+                    let removed = dict.remove_value(rem);
                     if dict.is_empty() {
                         self.children[c] = None;
                     }
+                    removed
                 }
             },
+        };
+        if removed.is_some() {
+            self.len -= 1;
         }
+        removed
+    }
+
+    /// Removes `word`, returning whether it was present.
+    pub fn remove(&mut self, word: &BStr) -> bool {
+        self.remove_value(word).is_some()
+    }
+
+    /// Returns the number of words.
+    pub fn len(&self) -> usize {
+        self.len
     }
 
     pub fn is_empty(&self) -> bool {
-        !self.val && self.children.values().all(Option::is_none)
+        self.len == 0
     }
 
-    pub fn contains(&self, word: &BStr) -> bool {
+    pub fn get(&self, word: &BStr) -> Option<&V> {
+        self.subtrie(word).and_then(|d| d.val.as_ref())
+    }
+
+    pub fn get_mut(&mut self, word: &BStr) -> Option<&mut V> {
         match word.split_first() {
-            None => self.val,
-            Some((&c, rem)) => match &self.children[c] {
-                None => false,
-                Some(dict) => dict.contains(rem),
-            },
+            None => self.val.as_mut(),
+            Some((&c, rem)) => self.children[c].as_mut().and_then(|d| d.get_mut(rem)),
         }
     }
 
+    pub fn contains(&self, word: &BStr) -> bool {
+        self.get(word).is_some()
+    }
+
     pub fn words(&self) -> Vec<BString> {
         let mut out = Vec::new();
         self.traverse(|w| out.push(w.to_owned()));
         out
     }
 
+    /// Returns a hash of the words, ignoring any values. Unlike [`hash::Hash`], it is the same on
+    /// every run and platform, so it can be saved to tell later which dictionary was used.
+    pub fn fingerprint(&self) -> u64 {
+        // 64-bit FNV-1a over each word followed by a newline
+        let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+        self.traverse(|w| {
+            for &b in w.to_string().as_bytes().iter().chain(b"\n") {
+                hash = (hash ^ u64::from(b)).wrapping_mul(0x0100_0000_01b3);
+            }
+        });
+        hash
+    }
+
     pub fn traverse<F>(&self, mut f: F)
     where
         F: FnMut(&BStr),
+    {
+        self.traverse_entries(|w, _| f(w));
+    }
+
+    pub fn traverse_entries<F>(&self, mut f: F)
+    where
+        F: FnMut(&BStr, &V),
     {
         let mut current_str = BString::default();
         self.traverse_impl(&mut current_str, &mut f);
@@ -368,10 +461,10 @@ impl Dict {
 
     fn traverse_impl<F>(&self, current_str: &mut BString, f: &mut F)
     where
-        F: FnMut(&BStr),
+        F: FnMut(&BStr, &V),
     {
-        if self.val {
-            f(&current_str);
+        if let Some(v) = &self.val {
+            f(current_str, v);
         }
         for (ch, v) in self.children.iter() {
             if let Some(d) = v {
@@ -385,6 +478,13 @@ impl Dict {
     pub fn try_traverse<F, E>(&self, mut f: F) -> Result<(), E>
     where
         F: FnMut(&BStr) -> Result<(), E>,
+    {
+        self.try_traverse_entries(|w, _| f(w))
+    }
+
+    pub fn try_traverse_entries<F, E>(&self, mut f: F) -> Result<(), E>
+    where
+        F: FnMut(&BStr, &V) -> Result<(), E>,
     {
         let mut current_str = BString::default();
         self.try_traverse_impl(&mut current_str, &mut f)
@@ -392,10 +492,10 @@ impl Dict {
 
     fn try_traverse_impl<F, E>(&self, current_str: &mut BString, f: &mut F) -> Result<(), E>
     where
-        F: FnMut(&BStr) -> Result<(), E>,
+        F: FnMut(&BStr, &V) -> Result<(), E>,
     {
-        if self.val {
-            f(&current_str)?;
+        if let Some(v) = &self.val {
+            f(current_str, v)?;
         }
         for (ch, v) in self.children.iter() {
             if let Some(d) = v {
@@ -407,62 +507,658 @@ impl Dict {
         Ok(())
     }
 
+    /// Removes empty branches and recomputes the word counts.
     fn prune(&mut self) {
+        self.len = self.val.is_some() as usize;
         for v in self.children.values_mut() {
             if let Some(d) = v {
                 d.prune();
                 if d.is_empty() {
                     *v = None;
+                } else {
+                    self.len += d.len;
                 }
             }
         }
     }
 
-    // pub fn iter(&self) -> impl Iterator<Item = BString> + '_ {
-    //     self.iter_impl().map(|w| BString(Vec::from(w)))
-    // }
+    fn update_len(&mut self) {
+        self.len = self.val.is_some() as usize
+            + self
+                .children
+                .iter()
+                .map(|(_, v)| v.as_ref().map_or(0, |d| d.len))
+                .sum::<usize>();
+    }
 
-    // fn iter_impl(&self) -> Box<dyn Iterator<Item = VecDeque<BChar>> + '_> {
-    //     Box::new(
-    //         self.val.then(Default::default).into_iter().chain(
-    //             self.children
-    //                 .iter()
-    //                 .filter_map(|(c, v)| Some(c).zip(v.as_ref()))
-    //                 .flat_map(|(c, v)| {
-    //                     v.iter_impl().map(move |mut w| {
-    //                         w.push_front(c);
-    //                         w
-    //                     })
-    //                 }),
-    //         ),
-    //     )
-    // }
+    /// Adds every word in `other`. Words already present keep their values.
+    pub fn union_with(&mut self, other: &Dict<V>)
+    where
+        V: Clone,
+    {
+        if self.val.is_none() {
+            self.val = other.val.clone();
+        }
+        for (ch, v) in self.children.iter_mut() {
+            match (v.as_mut(), &other.children[ch]) {
+                (Some(d), Some(o)) => d.union_with(o),
+                (None, Some(o)) => *v = Some(o.clone()),
+                (_, None) => {}
+            }
+        }
+        self.update_len();
+    }
 
-    // fn into_iter_impl(self) -> Box<dyn Iterator<Item = VecDeque<BChar>>> {
-    //     Box::new(
-    //         self.val.then(Default::default).into_iter().chain(
-    //             self.children
-    //                 .into_iter()
-    //                 .filter_map(|(c, v)| Some(c).zip(v))
-    //                 .flat_map(|(c, v)| {
-    //                     v.into_iter_impl().map(move |mut w| {
-    //                         w.push_front(c);
-    //                         w
-    //                     })
-    //                 }),
-    //         ),
-    //     )
-    // }
+    /// Removes every word not in `other`.
+    pub fn intersect_with<W>(&mut self, other: &Dict<W>) {
+        if other.val.is_none() {
+            self.val = None;
+        }
+        for (ch, v) in self.children.iter_mut() {
+            if let Some(d) = v {
+                if let Some(o) = &other.children[ch] {
+                    d.intersect_with(o);
+                }
+                if other.children[ch].is_none() || d.is_empty() {
+                    *v = None;
+                }
+            }
+        }
+        self.update_len();
+    }
+
+    /// Removes every word in `other`.
+    pub fn difference_with<W>(&mut self, other: &Dict<W>) {
+        if other.val.is_some() {
+            self.val = None;
+        }
+        for (ch, v) in self.children.iter_mut() {
+            if let (Some(d), Some(o)) = (v.as_mut(), &other.children[ch]) {
+                d.difference_with(o);
+                if d.is_empty() {
+                    *v = None;
+                }
+            }
+        }
+        self.update_len();
+    }
+
+    /// Keeps the words in exactly one of `self` and `other`.
+    pub fn symmetric_difference_with(&mut self, other: &Dict<V>)
+    where
+        V: Clone,
+    {
+        if other.val.is_some() {
+            self.val = match self.val {
+                Some(_) => None,
+                None => other.val.clone(),
+            };
+        }
+        for (ch, v) in self.children.iter_mut() {
+            match (v.as_mut(), &other.children[ch]) {
+                (Some(d), Some(o)) => {
+                    d.symmetric_difference_with(o);
+                    if d.is_empty() {
+                        *v = None;
+                    }
+                }
+                (None, Some(o)) => *v = Some(o.clone()),
+                (_, None) => {}
+            }
+        }
+        self.update_len();
+    }
+
+    pub fn union(&self, other: &Dict<V>) -> Dict<V>
+    where
+        V: Clone,
+    {
+        let mut out = self.clone();
+        out.union_with(other);
+        out
+    }
+
+    pub fn intersection<W>(&self, other: &Dict<W>) -> Dict<V>
+    where
+        V: Clone,
+    {
+        let mut out = Dict {
+            val: other.val.as_ref().and(self.val.clone()),
+            ..Default::default()
+        };
+        for (ch, v) in self.children.iter() {
+            if let (Some(d), Some(o)) = (v, &other.children[ch]) {
+                let child = d.intersection(o);
+                if !child.is_empty() {
+                    out.children[ch] = Some(Box::new(child));
+                }
+            }
+        }
+        out.update_len();
+        out
+    }
+
+    pub fn difference<W>(&self, other: &Dict<W>) -> Dict<V>
+    where
+        V: Clone,
+    {
+        let mut out = self.clone();
+        out.difference_with(other);
+        out
+    }
+
+    pub fn symmetric_difference(&self, other: &Dict<V>) -> Dict<V>
+    where
+        V: Clone,
+    {
+        let mut out = self.clone();
+        out.symmetric_difference_with(other);
+        out
+    }
+
+    fn raw_iter(&self) -> RawIter<'_, V> {
+        RawIter {
+            front: vec![(self, 0)],
+            front_str: BString::default(),
+            back: vec![(self, BChar::LENGTH + 1)],
+            back_str: BString::default(),
+            remaining: self.len,
+        }
+    }
+
+    /// Returns an iterator over the words in sorted order.
+    pub fn iter(&self) -> Iter<'_, V> {
+        Iter(self.raw_iter())
+    }
+
+    /// Returns an iterator over the words and their values in sorted order.
+    pub fn entries(&self) -> Entries<'_, V> {
+        Entries(self.raw_iter())
+    }
+
+    /// Removes and returns the first word in sorted order, with its value.
+    pub fn pop_first_entry(&mut self) -> Option<(BString, V)> {
+        let mut current_str = BString::default();
+        self.pop_first_impl(&mut current_str)
+            .map(|v| (current_str, v))
+    }
+
+    fn pop_first_impl(&mut self, current_str: &mut BString) -> Option<V> {
+        if let Some(val) = self.val.take() {
+            self.len -= 1;
+            return Some(val);
+        }
+        for (ch, v) in self.children.iter_mut() {
+            if let Some(d) = v {
+                current_str.push(ch);
+                if let Some(val) = d.pop_first_impl(current_str) {
+                    if d.is_empty() {
+                        *v = None;
+                    }
+                    self.len -= 1;
+                    return Some(val);
+                }
+                current_str.pop();
+            }
+        }
+        None
+    }
+
+    /// Removes and returns the last word in sorted order, with its value.
+    pub fn pop_last_entry(&mut self) -> Option<(BString, V)> {
+        let mut current_str = BString::default();
+        self.pop_last_impl(&mut current_str)
+            .map(|v| (current_str, v))
+    }
+
+    fn pop_last_impl(&mut self, current_str: &mut BString) -> Option<V> {
+        for (ch, v) in self.children.iter_mut().rev() {
+            if let Some(d) = v {
+                current_str.push(ch);
+                if let Some(val) = d.pop_last_impl(current_str) {
+                    if d.is_empty() {
+                        *v = None;
+                    }
+                    self.len -= 1;
+                    return Some(val);
+                }
+                current_str.pop();
+            }
+        }
+        let val = self.val.take();
+        if val.is_some() {
+            self.len -= 1;
+        }
+        val
+    }
+
+    /// Returns the trie of words that follow `prefix`, so `subtrie("ca")` contains `"t"` if this
+    /// contains `"cat"`.
+    pub fn subtrie(&self, prefix: &BStr) -> Option<&Dict<V>> {
+        match prefix.split_first() {
+            None => Some(self),
+            Some((&c, rem)) => self.children[c].as_ref().and_then(|d| d.subtrie(rem)),
+        }
+    }
+
+    pub fn has_prefix(&self, prefix: &BStr) -> bool {
+        self.subtrie(prefix).is_some_and(|d| !d.is_empty())
+    }
+
+    pub fn words_with_prefix(&self, prefix: &BStr) -> Vec<BString> {
+        let mut out = Vec::new();
+        if let Some(d) = self.subtrie(prefix) {
+            let mut current_str = prefix.to_owned();
+            d.traverse_impl(&mut current_str, &mut |w: &BStr, _: &V| {
+                out.push(w.to_owned())
+            });
+        }
+        out
+    }
+
+    /// Calls `f` on each word whose length in letters is within `len`, pruning the search below
+    /// the maximum length.
+    pub fn traverse_len<R, F>(&self, len: R, mut f: F)
+    where
+        R: RangeBounds<usize>,
+        F: FnMut(&BStr),
+    {
+        let mut current_str = BString::default();
+        self.traverse_len_impl(&len, &mut current_str, &mut f);
+    }
+
+    fn traverse_len_impl<R, F>(&self, len: &R, current_str: &mut BString, f: &mut F)
+    where
+        R: RangeBounds<usize>,
+        F: FnMut(&BStr),
+    {
+        if self.val.is_some() && len.contains(&current_str.0.len()) {
+            f(current_str);
+        }
+        let below_max = match len.end_bound() {
+            Bound::Included(&max) => current_str.0.len() < max,
+            Bound::Excluded(&max) => current_str.0.len() + 1 < max,
+            Bound::Unbounded => true,
+        };
+        if !below_max {
+            return;
+        }
+        for (ch, v) in self.children.iter() {
+            if let Some(d) = v {
+                current_str.push(ch);
+                d.traverse_len_impl(len, current_str, f);
+                current_str.pop();
+            }
+        }
+    }
+
+    pub fn words_with_len<R: RangeBounds<usize>>(&self, len: R) -> Vec<BString> {
+        let mut out = Vec::new();
+        self.traverse_len(len, |w| out.push(w.to_owned()));
+        out
+    }
+
+    /// Returns the words that use exactly the letters in `letters`, in any order.
+    pub fn anagrams(&self, letters: &BStr) -> Dict {
+        self.sub_anagrams(letters, letters.len())
+    }
+
+    /// Returns the words of at least `min_len` letters that can be made from `letters`, using
+    /// each letter no more times than it appears.
+    pub fn sub_anagrams(&self, letters: &BStr, min_len: usize) -> Dict {
+        let mut counts = EnumMap::<BChar, usize>::default();
+        for &c in letters.0.iter() {
+            counts[c] += 1;
+        }
+        let mut out = Dict::new();
+        let mut current_str = BString::default();
+        self.sub_anagrams_impl(&mut counts, min_len, &mut current_str, &mut out);
+        out
+    }
+
+    fn sub_anagrams_impl(
+        &self,
+        counts: &mut EnumMap<BChar, usize>,
+        min_len: usize,
+        current_str: &mut BString,
+        out: &mut Dict,
+    ) {
+        if self.val.is_some() && current_str.len() >= min_len {
+            out.insert(current_str);
+        }
+        for (ch, v) in self.children.iter() {
+            if let Some(d) = v {
+                if counts[ch] == 0 {
+                    continue;
+                }
+                counts[ch] -= 1;
+                current_str.push(ch);
+                d.sub_anagrams_impl(counts, min_len, current_str, out);
+                current_str.pop();
+                counts[ch] += 1;
+            }
+        }
+    }
+
+    /// Returns the words within edit distance `max_dist` of `word`, counting each inserted,
+    /// deleted or substituted letter as one edit. The results are sorted by distance, then
+    /// alphabetically.
+    ///
+    /// To only suggest words that are on a board, search the trie from [`Board::words_trie`].
+    pub fn fuzzy(&self, word: &BStr, max_dist: usize) -> Vec<(BString, usize)> {
+        let row: Vec<usize> = (0..=word.len()).collect();
+        let mut out = Vec::new();
+        let mut current_str = BString::default();
+        self.fuzzy_impl(word, max_dist, &row, &mut current_str, &mut out);
+        out.sort_by_key(|&(_, dist)| dist);
+        out
+    }
+
+    // `row[j]` is the edit distance between `current_str` and the first `j` letters of `word`
+    fn fuzzy_impl(
+        &self,
+        word: &BStr,
+        max_dist: usize,
+        row: &[usize],
+        current_str: &mut BString,
+        out: &mut Vec<(BString, usize)>,
+    ) {
+        if self.val.is_some() && row[word.len()] <= max_dist {
+            out.push((current_str.clone(), row[word.len()]));
+        }
+        if row.iter().all(|&dist| dist > max_dist) {
+            return;
+        }
+        for (ch, v) in self.children.iter() {
+            if let Some(d) = v {
+                let mut next_row = Vec::with_capacity(row.len());
+                next_row.push(row[0] + 1);
+                for (j, &c) in word.0.iter().enumerate() {
+                    let substitute = row[j] + (c != ch) as usize;
+                    next_row.push(substitute.min(row[j + 1] + 1).min(next_row[j] + 1));
+                }
+                current_str.push(ch);
+                d.fuzzy_impl(word, max_dist, &next_row, current_str, out);
+                current_str.pop();
+            }
+        }
+    }
+
+    /// Returns the words matching `pattern`.
+    pub fn matching(&self, pattern: &Pattern) -> Dict {
+        let mut out = Dict::new();
+        let mut current_str = BString::default();
+        self.matching_impl(&pattern.0, &mut current_str, &mut out);
+        out
+    }
+
+    fn matching_impl(&self, pattern: &[PatternToken], current_str: &mut BString, out: &mut Dict) {
+        match pattern.split_first() {
+            None => {
+                if self.val.is_some() {
+                    out.insert(current_str);
+                }
+            }
+            Some((PatternToken::Char(c), rem)) => {
+                if let Some(d) = &self.children[*c] {
+                    current_str.push(*c);
+                    d.matching_impl(rem, current_str, out);
+                    current_str.pop();
+                }
+            }
+            Some((PatternToken::Any, rem)) => {
+                for (ch, v) in self.children.iter() {
+                    if let Some(d) = v {
+                        current_str.push(ch);
+                        d.matching_impl(rem, current_str, out);
+                        current_str.pop();
+                    }
+                }
+            }
+            Some((PatternToken::Star, rem)) => {
+                self.matching_impl(rem, current_str, out);
+                for (ch, v) in self.children.iter() {
+                    if let Some(d) = v {
+                        current_str.push(ch);
+                        d.matching_impl(pattern, current_str, out);
+                        current_str.pop();
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Walks a trie in sorted order from both ends with an explicit stack.
+#[derive(Debug, Clone)]
+struct RawIter<'a, V> {
+    // each frame is a node and 0 for the node itself, or 1 + the index of the next child
+    front: Vec<(&'a Dict<V>, usize)>,
+    front_str: BString,
+    // each frame is a node and 1 + the index of the next child, or 0 for the node itself
+    back: Vec<(&'a Dict<V>, usize)>,
+    back_str: BString,
+    remaining: usize,
+}
+
+impl<'a, V> Iterator for RawIter<'a, V> {
+    type Item = (BString, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        while let Some((node, pos)) = self.front.last_mut() {
+            let node: &'a Dict<V> = node;
+            if *pos == 0 {
+                *pos = 1;
+                if let Some(v) = &node.val {
+                    self.remaining -= 1;
+                    return Some((self.front_str.clone(), v));
+                }
+            } else if *pos > BChar::LENGTH {
+                self.front.pop();
+                self.front_str.pop();
+            } else {
+                let ch = BChar::from_usize(*pos - 1);
+                *pos += 1;
+                if let Some(d) = &node.children[ch] {
+                    self.front.push((d, 0));
+                    self.front_str.push(ch);
+                }
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, V> DoubleEndedIterator for RawIter<'a, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        while let Some((node, pos)) = self.back.last_mut() {
+            let node: &'a Dict<V> = node;
+            if *pos == 0 {
+                self.back.pop();
+                let entry = node.val.as_ref().map(|v| (self.back_str.clone(), v));
+                self.back_str.pop();
+                if entry.is_some() {
+                    self.remaining -= 1;
+                    return entry;
+                }
+            } else {
+                *pos -= 1;
+                if *pos > 0 {
+                    let ch = BChar::from_usize(*pos - 1);
+                    if let Some(d) = &node.children[ch] {
+                        self.back.push((d, BChar::LENGTH + 1));
+                        self.back_str.push(ch);
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+/// An iterator over the words in a [`Dict`] in sorted order.
+#[derive(Debug, Clone)]
+pub struct Iter<'a, V = ()>(RawIter<'a, V>);
+
+impl<'a, V> Iterator for Iter<'a, V> {
+    type Item = BString;
+
+    fn next(&mut self) -> Option<BString> {
+        self.0.next().map(|(w, _)| w)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<'a, V> DoubleEndedIterator for Iter<'a, V> {
+    fn next_back(&mut self) -> Option<BString> {
+        self.0.next_back().map(|(w, _)| w)
+    }
+}
+
+impl<'a, V> ExactSizeIterator for Iter<'a, V> {}
+
+impl<'a, V> FusedIterator for Iter<'a, V> {}
+
+impl<'a, V> IntoIterator for &'a Dict<V> {
+    type Item = BString;
+    type IntoIter = Iter<'a, V>;
+
+    fn into_iter(self) -> Iter<'a, V> {
+        self.iter()
+    }
+}
+
+/// An iterator over the words in a [`Dict`] and their values in sorted order.
+#[derive(Debug, Clone)]
+pub struct Entries<'a, V>(RawIter<'a, V>);
+
+impl<'a, V> Iterator for Entries<'a, V> {
+    type Item = (BString, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<'a, V> DoubleEndedIterator for Entries<'a, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back()
+    }
+}
+
+impl<'a, V> ExactSizeIterator for Entries<'a, V> {}
+
+impl<'a, V> FusedIterator for Entries<'a, V> {}
+
+/// An owning iterator over the words in a [`Dict`] in sorted order, which removes each word from
+/// the trie as it is yielded.
+#[derive(Debug, Clone)]
+pub struct IntoIter(Dict);
+
+impl Iterator for IntoIter {
+    type Item = BString;
+
+    fn next(&mut self) -> Option<BString> {
+        self.0.pop_first()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.0.len(), Some(self.0.len()))
+    }
+}
+
+impl DoubleEndedIterator for IntoIter {
+    fn next_back(&mut self) -> Option<BString> {
+        self.0.pop_last()
+    }
+}
+
+impl ExactSizeIterator for IntoIter {}
+
+impl FusedIterator for IntoIter {}
+
+impl IntoIterator for Dict {
+    type Item = BString;
+    type IntoIter = IntoIter;
+
+    fn into_iter(self) -> IntoIter {
+        IntoIter(self)
+    }
+}
+
+impl<V: Clone> BitOr<&Dict<V>> for &Dict<V> {
+    type Output = Dict<V>;
+
+    fn bitor(self, rhs: &Dict<V>) -> Dict<V> {
+        self.union(rhs)
+    }
+}
+
+impl<V: Clone> BitOrAssign<&Dict<V>> for Dict<V> {
+    fn bitor_assign(&mut self, rhs: &Dict<V>) {
+        self.union_with(rhs)
+    }
+}
+
+impl<V: Clone, W> BitAnd<&Dict<W>> for &Dict<V> {
+    type Output = Dict<V>;
+
+    fn bitand(self, rhs: &Dict<W>) -> Dict<V> {
+        self.intersection(rhs)
+    }
+}
+
+impl<V, W> BitAndAssign<&Dict<W>> for Dict<V> {
+    fn bitand_assign(&mut self, rhs: &Dict<W>) {
+        self.intersect_with(rhs)
+    }
+}
+
+impl<V: Clone, W> Sub<&Dict<W>> for &Dict<V> {
+    type Output = Dict<V>;
+
+    fn sub(self, rhs: &Dict<W>) -> Dict<V> {
+        self.difference(rhs)
+    }
+}
+
+impl<V, W> SubAssign<&Dict<W>> for Dict<V> {
+    fn sub_assign(&mut self, rhs: &Dict<W>) {
+        self.difference_with(rhs)
+    }
+}
+
+impl<V: Clone> BitXor<&Dict<V>> for &Dict<V> {
+    type Output = Dict<V>;
+
+    fn bitxor(self, rhs: &Dict<V>) -> Dict<V> {
+        self.symmetric_difference(rhs)
+    }
+}
+
+impl<V: Clone> BitXorAssign<&Dict<V>> for Dict<V> {
+    fn bitxor_assign(&mut self, rhs: &Dict<V>) {
+        self.symmetric_difference_with(rhs)
+    }
 }
 
-// impl IntoIterator for Dict {
-//     type Item = BString;
-//     type IntoIter = Box<dyn Iterator<Item = BString>>;
-//     fn into_iter(self) -> Self::IntoIter {
-//         Box::new(self.into_iter_impl().map(|w| BString(Vec::from(w))))
-//     }
-// }
-//
 impl<'a> FromIterator<&'a BStr> for Dict {
     fn from_iter<I: IntoIterator<Item = &'a BStr>>(iter: I) -> Self {
         let mut dict = Dict::default();
@@ -499,10 +1195,123 @@ impl Extend<BString> for Dict {
     }
 }
 
+impl<'a, V> FromIterator<(&'a BStr, V)> for Dict<V> {
+    fn from_iter<I: IntoIterator<Item = (&'a BStr, V)>>(iter: I) -> Self {
+        let mut dict = Dict::default();
+        dict.extend(iter);
+        dict
+    }
+}
+
+impl<'a, V> Extend<(&'a BStr, V)> for Dict<V> {
+    fn extend<T: IntoIterator<Item = (&'a BStr, V)>>(&mut self, iter: T) {
+        for (word, value) in iter {
+            self.insert_value(word, value);
+        }
+    }
+}
+
+impl<V> FromIterator<(BString, V)> for Dict<V> {
+    fn from_iter<I: IntoIterator<Item = (BString, V)>>(iter: I) -> Self {
+        let mut dict = Dict::default();
+        dict.extend(iter);
+        dict
+    }
+}
+
+impl<V> Extend<(BString, V)> for Dict<V> {
+    fn extend<T: IntoIterator<Item = (BString, V)>>(&mut self, iter: T) {
+        for (word, value) in iter {
+            self.insert_value(&word, value);
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PatternToken {
+    Char(BChar),
+    Any,
+    Star,
+}
+
+/// A word pattern for [`Dict::matching`], in which `?` stands for any single letter and `*` for
+/// any run of letters, including none. For example, `C?T` matches `cat` and `cot`, and `*ING`
+/// matches every word ending in `ing`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pattern(Vec<PatternToken>);
+
+impl FromStr for Pattern {
+    type Err = ParseBoggleError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut out = Vec::new();
+        let mut run = String::new();
+        for c in s.chars() {
+            let token = match c {
+                '?' => PatternToken::Any,
+                '*' => PatternToken::Star,
+                _ => {
+                    run.push(c);
+                    continue;
+                }
+            };
+            out.extend(
+                run.parse::<BString>()?
+                    .0
+                    .into_iter()
+                    .map(PatternToken::Char),
+            );
+            run.clear();
+            // `**` matches the same words as `*`, but each extra star multiplies the work done
+            if token == PatternToken::Star && out.last() == Some(&PatternToken::Star) {
+                continue;
+            }
+            out.push(token);
+        }
+        out.extend(
+            run.parse::<BString>()?
+                .0
+                .into_iter()
+                .map(PatternToken::Char),
+        );
+        Ok(Pattern(out))
+    }
+}
+
+/// Definitions of words, for showing to players who challenge a word.
+pub type Definitions = Dict<String>;
+
 pub type Dice = [[BChar; 6]; 16];
 
-#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Debug, Clone, Copy, Default)]
+/// A set of dice together with the metadata stored alongside it in a dice file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiceSet {
+    pub name: Option<String>,
+    pub dice: Dice,
+}
+
+impl From<Dice> for DiceSet {
+    fn from(dice: Dice) -> Self {
+        DiceSet { name: None, dice }
+    }
+}
+
+impl fmt::Display for DiceSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(name) = &self.name {
+            writeln!(f, "name: {}", io::escape_header(name))?;
+        }
+        writeln!(f, "size: 4x4")?;
+        writeln!(f)?;
+        for die in self.dice.iter() {
+            let faces: Vec<String> = die.iter().map(|c| c.to_string()).collect();
+            writeln!(f, "{}", faces.join(" "))?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
 #[repr(transparent)]
 pub struct Board([[BChar; 4]; 4]);
 
@@ -549,12 +1358,12 @@ impl Board {
     //     }
     // }
 
-    pub fn words_trie(&self, dict: &Dict) -> Dict {
+    pub fn words_trie<V>(&self, dict: &Dict<V>) -> Dict {
         let mut visited = [[false; 4]; 4];
         // let mut out = Dict::default();
         // out.val = dict.val;
         let mut out = Dict {
-            val: dict.val,
+            val: dict.val.as_ref().map(|_| ()),
             ..Default::default()
         };
         for r in 0..4 {
@@ -575,17 +1384,17 @@ impl Board {
         out
     }
 
-    fn visit_pos_trie(
+    fn visit_pos_trie<V>(
         &self,
         row: usize,
         col: usize,
         visited: &mut [[bool; 4]; 4],
-        dict: &Dict,
+        dict: &Dict<V>,
         out: &mut Dict,
     ) {
         visited[row][col] = true;
-        if dict.val {
-            out.val = true;
+        if dict.val.is_some() {
+            out.val = Some(());
         }
         for (r, c) in neighbours((row, col)) {
             if visited[r][c] {
@@ -608,7 +1417,51 @@ impl Board {
     pub fn contains(&self, word: &BStr) -> bool {
         let mut dict = Dict::new();
         dict.insert(word);
-        return !self.words_trie(&dict).is_empty()
+        !self.words_trie(&dict).is_empty()
+    }
+
+    /// Returns the positions of the tiles spelling out `word`, as `(row, column)` pairs, if it is
+    /// on the board.
+    pub fn find_path(&self, word: &BStr) -> Option<Vec<(usize, usize)>> {
+        let (&first, _) = word.split_first()?;
+        let mut visited = [[false; 4]; 4];
+        let mut path = Vec::with_capacity(word.len());
+        for r in 0..4 {
+            for c in 0..4 {
+                if self.0[r][c] == first && self.find_path_impl(r, c, word, &mut visited, &mut path)
+                {
+                    return Some(path);
+                }
+            }
+        }
+        None
+    }
+
+    fn find_path_impl(
+        &self,
+        row: usize,
+        col: usize,
+        word: &BStr,
+        visited: &mut [[bool; 4]; 4],
+        path: &mut Vec<(usize, usize)>,
+    ) -> bool {
+        let rem = match word.split_first() {
+            Some((&c, rem)) if c == self.0[row][col] => rem,
+            _ => return false,
+        };
+        visited[row][col] = true;
+        path.push((row, col));
+        if rem.is_empty() {
+            return true;
+        }
+        for (r, c) in neighbours((row, col)) {
+            if !visited[r][c] && self.find_path_impl(r, c, rem, visited, path) {
+                return true;
+            }
+        }
+        visited[row][col] = false;
+        path.pop();
+        false
     }
 }
 
@@ -645,6 +1498,56 @@ impl fmt::Display for Board {
     }
 }
 
+/// Parses a board from the layout written by its `Display` impl, one tile per word, or from four
+/// rows of four letters such as `RLTT EFOE MPIT EHVL`.
+impl FromStr for Board {
+    type Err = ParseBoardError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let words: Vec<&str> = s.split_whitespace().collect();
+        let tiles: Vec<BChar> = match words.len() {
+            16 => words
+                .iter()
+                .map(|w| match w.parse::<BString>()?.0[..] {
+                    [c] => Ok(c),
+                    _ => Err(ParseBoardError),
+                })
+                .collect::<Result<_, _>>()?,
+            4 => words
+                .iter()
+                .map(|w| match w.parse::<BString>()? {
+                    row if row.len() == 4 => Ok(row.0),
+                    _ => Err(ParseBoardError),
+                })
+                .collect::<Result<Vec<_>, _>>()?
+                .concat(),
+            _ => return Err(ParseBoardError),
+        };
+        let mut board = Board::default();
+        for (i, c) in tiles.into_iter().enumerate() {
+            board.0[i / 4][i % 4] = c;
+        }
+        Ok(board)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseBoardError;
+
+impl fmt::Display for ParseBoardError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "expected four rows of four letters")
+    }
+}
+
+impl error::Error for ParseBoardError {}
+
+impl From<ParseBoggleError> for ParseBoardError {
+    fn from(_: ParseBoggleError) -> Self {
+        ParseBoardError
+    }
+}
+
 impl Index<usize> for Board {
     type Output = [BChar; 4];
     fn index(&self, index: usize) -> &Self::Output {
@@ -662,7 +1565,7 @@ pub fn roll<R: Rng + ?Sized>(dice: &Dice, rng: &mut R) -> Board {
 
 pub fn score(word: &str) -> u8 {
     match word.len() {
-        0 | 1 | 2 => 0,
+        0..=2 => 0,
         3 | 4 => 1,
         5 => 2,
         6 => 3,
@@ -670,3 +1573,76 @@ pub fn score(word: &str) -> u8 {
         _ => 11,
     }
 }
+
+/// A way of scoring words.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Scoring {
+    /// The standard table used by [`score`].
+    #[default]
+    Classic,
+    /// The table from Big Boggle, where three letter words don't score.
+    Big,
+    /// One point per letter.
+    Letters,
+}
+
+impl Scoring {
+    pub const ALL: [Scoring; 3] = [Scoring::Classic, Scoring::Big, Scoring::Letters];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Scoring::Classic => "classic",
+            Scoring::Big => "big",
+            Scoring::Letters => "letters",
+        }
+    }
+
+    pub fn score(self, word: &str) -> u32 {
+        match self {
+            Scoring::Classic => score(word) as u32,
+            Scoring::Big => match word.len() {
+                0..=3 => 0,
+                4 => 1,
+                5 => 2,
+                6 => 3,
+                7 => 5,
+                _ => 11,
+            },
+            Scoring::Letters => word.len() as u32,
+        }
+    }
+}
+
+impl fmt::Display for Scoring {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Scoring {
+    type Err = ParseScoringError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Scoring::ALL
+            .iter()
+            .copied()
+            .find(|scoring| scoring.name() == s)
+            .ok_or(ParseScoringError)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseScoringError;
+
+impl fmt::Display for ParseScoringError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "unknown scoring preset, expected one of classic, big or letters"
+        )
+    }
+}
+
+impl error::Error for ParseScoringError {}
@@ -0,0 +1,141 @@
+use crate::*;
+
+/// Follows a word across a board as it is typed, one letter at a time.
+///
+/// After each letter it knows every way of tracing the letters so far on the board, and whether
+/// any word in the dictionary starts with them, so that a frontend can highlight the tiles being
+/// spelled out and warn as soon as the word can't be finished.
+#[derive(Debug, Clone)]
+pub struct PathMatcher<'a, V = ()> {
+    board: &'a Board,
+    dict: &'a Dict<V>,
+    prefix: BString,
+    /// The paths and dictionary node for each letter typed, so that letters can be taken back.
+    levels: Vec<Level<'a, V>>,
+}
+
+#[derive(Debug, Clone)]
+struct Level<'a, V> {
+    paths: Vec<Vec<(usize, usize)>>,
+    node: Option<&'a Dict<V>>,
+}
+
+impl<'a, V> PathMatcher<'a, V> {
+    pub fn new(board: &'a Board, dict: &'a Dict<V>) -> Self {
+        PathMatcher {
+            board,
+            dict,
+            prefix: BString::default(),
+            levels: Vec::new(),
+        }
+    }
+
+    /// The letters typed so far.
+    pub fn prefix(&self) -> &BStr {
+        &self.prefix
+    }
+
+    /// Adds a letter, returning whether the prefix is still on the board.
+    pub fn push(&mut self, ch: BChar) -> bool {
+        let paths = match self.levels.last() {
+            None => {
+                let mut paths = Vec::new();
+                for r in 0..4 {
+                    for c in 0..4 {
+                        if self.board[r][c] == ch {
+                            paths.push(vec![(r, c)]);
+                        }
+                    }
+                }
+                paths
+            }
+            Some(level) => {
+                let mut paths = Vec::new();
+                for path in level.paths.iter() {
+                    let &last = path.last().unwrap();
+                    for (r, c) in neighbours(last) {
+                        if self.board[r][c] == ch && !path.contains(&(r, c)) {
+                            let mut next = path.clone();
+                            next.push((r, c));
+                            paths.push(next);
+                        }
+                    }
+                }
+                paths
+            }
+        };
+        let node = match self.levels.last() {
+            None => self.dict.children[ch].as_deref(),
+            Some(level) => level.node.and_then(|d| d.children[ch].as_deref()),
+        };
+        self.prefix.push(ch);
+        let on_board = !paths.is_empty();
+        self.levels.push(Level { paths, node });
+        on_board
+    }
+
+    /// Takes back the last letter.
+    pub fn pop(&mut self) -> Option<BChar> {
+        self.levels.pop()?;
+        self.prefix.pop()
+    }
+
+    /// Changes the prefix to `word`, keeping the work done for the letters it shares with the
+    /// current prefix. This suits text fields where any part of the word may be edited.
+    pub fn set(&mut self, word: &BStr) -> bool {
+        let common = self
+            .prefix
+            .0
+            .iter()
+            .zip(word.0.iter())
+            .take_while(|(a, b)| a == b)
+            .count();
+        while self.prefix.len() > common {
+            self.pop();
+        }
+        for &c in word.0.iter().skip(common) {
+            self.push(c);
+        }
+        self.is_on_board()
+    }
+
+    pub fn clear(&mut self) {
+        self.prefix = BString::default();
+        self.levels.clear();
+    }
+
+    /// Every path of distinct, adjacent tiles spelling out the prefix, as `(row, column)` pairs.
+    pub fn paths(&self) -> &[Vec<(usize, usize)>] {
+        self.levels.last().map_or(&[], |level| &level.paths)
+    }
+
+    /// The tiles that are on at least one of the paths.
+    pub fn positions(&self) -> [[bool; 4]; 4] {
+        let mut positions = [[false; 4]; 4];
+        for &(r, c) in self.paths().iter().flatten() {
+            positions[r][c] = true;
+        }
+        positions
+    }
+
+    /// Whether the prefix can be traced on the board. The empty prefix always can.
+    pub fn is_on_board(&self) -> bool {
+        self.levels.is_empty() || !self.paths().is_empty()
+    }
+
+    /// Whether any word in the dictionary starts with the prefix, including the prefix itself.
+    pub fn extends(&self) -> bool {
+        match self.levels.last() {
+            None => !self.dict.is_empty(),
+            Some(level) => level.node.is_some_and(|d| !d.is_empty()),
+        }
+    }
+
+    /// Whether the prefix is a word in the dictionary.
+    pub fn is_word(&self) -> bool {
+        match self.levels.last() {
+            None => self.dict.val.is_some(),
+            Some(level) => level.node.is_some_and(|d| d.val.is_some()),
+        }
+    }
+}
@@ -0,0 +1,49 @@
+//! Messages for playing over the network.
+//!
+//! Each message is a JSON object on its own line, with a `type` field naming the message, for
+//! example `{"type":"word","word":"cat"}`.
+
+use crate::round::{RoundResult, Verdict};
+use crate::*;
+use std::collections::BTreeMap;
+
+/// The port the server listens on unless told otherwise.
+pub const DEFAULT_PORT: u16 = 7878;
+
+/// A message from a player to the server.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClientMessage {
+    /// Joins a room, creating it if it doesn't exist. This must be the first message sent.
+    Join { room: String, name: String },
+    /// Starts a round for everyone in the room, unless one is already running.
+    Start,
+    /// Submits a word in the current round.
+    Word { word: String },
+}
+
+/// A message from the server to a player.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMessage {
+    /// The players in the room, sent whenever someone joins or leaves.
+    Players { room: String, players: Vec<String> },
+    /// A round has started, and ends after `millis` milliseconds.
+    Start {
+        board: Board,
+        millis: u64,
+        scoring: Scoring,
+        min_len: usize,
+    },
+    /// What became of a word the player submitted.
+    Verdict { word: String, verdict: Verdict },
+    /// How many words each player has found so far this round.
+    Progress { found: BTreeMap<String, usize> },
+    /// Every player's results once the round is over, with words found by more than one player
+    /// cancelled.
+    Results {
+        results: BTreeMap<String, RoundResult>,
+    },
+    /// Something the player sent could not be handled.
+    Error { message: String },
+}
@@ -0,0 +1,197 @@
+//! Rooms of players sharing a board, as a server runs them.
+//!
+//! A [`Room`] only keeps track of who is playing and what they have found; it sends messages
+//! through the channel each player joined with and is told the time by its caller, so that it
+//! doesn't depend on how players are connected.
+
+use crate::filter::FilteredDict;
+use crate::protocol::ServerMessage;
+use crate::round::*;
+use crate::*;
+use std::collections::BTreeMap;
+use std::sync::mpsc::Sender;
+use std::time::Instant;
+
+/// A room that players join by name, in which everyone plays the same board against the same
+/// timer.
+#[derive(Debug)]
+pub struct Room<'a> {
+    name: String,
+    dict: &'a FilteredDict<'a>,
+    scoring: Scoring,
+    /// Where to send messages for each player.
+    players: BTreeMap<String, Sender<ServerMessage>>,
+    round: Option<RoomRound<'a>>,
+}
+
+#[derive(Debug)]
+struct RoomRound<'a> {
+    /// Tells a timer whether its round is still the one running.
+    id: u64,
+    board: Board,
+    deadline: Instant,
+    /// Each player's words, kept for players who leave part way through in case they come back.
+    checkers: BTreeMap<String, Checker<'a>>,
+}
+
+impl<'a> Room<'a> {
+    pub fn new(name: &str, dict: &'a FilteredDict<'a>, scoring: Scoring) -> Self {
+        Room {
+            name: name.to_string(),
+            dict,
+            scoring,
+            players: BTreeMap::new(),
+            round: None,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.players.is_empty()
+    }
+
+    /// The id of the round running, if there is one.
+    pub fn round_id(&self) -> Option<u64> {
+        self.round.as_ref().map(|round| round.id)
+    }
+
+    /// Adds a player, returning `false` if someone with the same name is already in the room.
+    /// Players joining part way through a round can still play the rest of it, and those
+    /// rejoining keep the words they already found.
+    pub fn join(&mut self, name: &str, out: Sender<ServerMessage>, now: Instant) -> bool {
+        if self.players.contains_key(name) {
+            return false;
+        }
+        self.players.insert(name.to_string(), out);
+        self.broadcast_players();
+        if let Some(round) = &mut self.round {
+            let (board, dict) = (round.board, self.dict);
+            round
+                .checkers
+                .entry(name.to_string())
+                .or_insert_with(|| Checker::new(&board, dict));
+            let message = self.start_message(now);
+            self.send(name, message);
+            self.broadcast_progress();
+        }
+        true
+    }
+
+    pub fn leave(&mut self, name: &str) {
+        if self.players.remove(name).is_some() && !self.players.is_empty() {
+            self.broadcast_players();
+        }
+    }
+
+    /// Starts a round on `board` for everyone in the room, unless one is already running.
+    /// Returns whether it started; the caller should call [`Room::finish_round`] at `deadline`.
+    pub fn start(&mut self, id: u64, board: Board, deadline: Instant, now: Instant) -> bool {
+        if self.round.is_some() {
+            return false;
+        }
+        let dict = self.dict;
+        self.round = Some(RoomRound {
+            id,
+            board,
+            deadline,
+            checkers: self
+                .players
+                .keys()
+                .map(|name| (name.clone(), Checker::new(&board, dict)))
+                .collect(),
+        });
+        let message = self.start_message(now);
+        self.broadcast(&message);
+        true
+    }
+
+    /// Checks a word a player entered at `now`, telling them how it did, or why it couldn't be
+    /// checked.
+    pub fn word(&mut self, name: &str, word: String, now: Instant) {
+        // the timer may not have ended the round yet
+        let verdict = match &mut self.round {
+            Some(round) if now >= round.deadline => Err("the round is over"),
+            Some(round) => round
+                .checkers
+                .get_mut(name)
+                .map(|checker| checker.check(&word))
+                .ok_or("no round is running"),
+            None => Err("no round is running"),
+        };
+        match verdict {
+            Ok(verdict) => {
+                self.send(name, ServerMessage::Verdict { word, verdict });
+                if verdict == Verdict::Correct {
+                    self.broadcast_progress();
+                }
+            }
+            Err(message) => self.error(name, message),
+        }
+    }
+
+    /// Tells a player something they sent could not be handled.
+    pub fn error(&mut self, name: &str, message: &str) {
+        self.send(
+            name,
+            ServerMessage::Error {
+                message: message.to_string(),
+            },
+        );
+    }
+
+    /// Ends the round, cancelling the words found by more than one player, and sends everyone
+    /// the results.
+    pub fn finish_round(&mut self) {
+        let round = match self.round.take() {
+            Some(round) => round,
+            None => return,
+        };
+        let names: Vec<String> = round.checkers.keys().cloned().collect();
+        let mut results: Vec<RoundResult> =
+            round.checkers.into_values().map(Checker::finish).collect();
+        cancel_duplicates(&mut results);
+        self.broadcast(&ServerMessage::Results {
+            results: names.into_iter().zip(results).collect(),
+        });
+    }
+
+    fn send(&self, name: &str, message: ServerMessage) {
+        if let Some(player) = self.players.get(name) {
+            // a player whose connection failed is removed when they leave
+            let _ = player.send(message);
+        }
+    }
+
+    fn broadcast(&self, message: &ServerMessage) {
+        for player in self.players.values() {
+            let _ = player.send(message.clone());
+        }
+    }
+
+    fn broadcast_players(&self) {
+        self.broadcast(&ServerMessage::Players {
+            room: self.name.clone(),
+            players: self.players.keys().cloned().collect(),
+        });
+    }
+
+    fn broadcast_progress(&self) {
+        if let Some(round) = &self.round {
+            let found = round
+                .checkers
+                .iter()
+                .map(|(name, checker)| (name.clone(), checker.result().correct.len()))
+                .collect();
+            self.broadcast(&ServerMessage::Progress { found });
+        }
+    }
+
+    fn start_message(&self, now: Instant) -> ServerMessage {
+        let round = self.round.as_ref().unwrap();
+        ServerMessage::Start {
+            board: round.board,
+            millis: round.deadline.saturating_duration_since(now).as_millis() as u64,
+            scoring: self.scoring,
+            min_len: self.dict.min_len,
+        }
+    }
+}
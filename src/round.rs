@@ -0,0 +1,220 @@
+use crate::filter::FilteredDict;
+use crate::*;
+use std::time::{Duration, Instant};
+
+/// A single round of Boggle: the board that was played and the words submitted for it, in the
+/// order they were entered.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Round {
+    pub board: Board,
+    pub words: Vec<String>,
+}
+
+/// The outcome of checking a [`Round`] against a dictionary.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct RoundResult {
+    /// Words that scored.
+    pub correct: Vec<String>,
+    /// Dictionary words that were repeated or are not on the board.
+    pub not_present: Vec<String>,
+    /// Words shorter than the minimum length.
+    pub too_short: Vec<String>,
+    /// Words not in the dictionary.
+    pub not_word: Vec<String>,
+    /// Words containing letters that cannot appear on a board, such as a `Q` without a `U`.
+    pub not_bword: Vec<String>,
+    /// Words on the board that were not found.
+    pub missed: Dict,
+    /// Words that would have scored, but were also found by another player.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub cancelled: Vec<String>,
+    /// Dictionary words that don't score because they are on the blocklist.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub blocked: Vec<String>,
+}
+
+impl RoundResult {
+    pub fn score(&self) -> u32 {
+        self.score_with(Scoring::Classic)
+    }
+
+    /// The score that was available on the board, including the words that were found.
+    pub fn max_score(&self) -> u32 {
+        self.max_score_with(Scoring::Classic)
+    }
+
+    pub fn score_with(&self, scoring: Scoring) -> u32 {
+        self.correct.iter().map(|w| scoring.score(w)).sum()
+    }
+
+    pub fn max_score_with(&self, scoring: Scoring) -> u32 {
+        let mut sum = self.score_with(scoring);
+        sum += self.cancelled.iter().map(|w| scoring.score(w)).sum::<u32>();
+        self.missed
+            .traverse(|w| sum += scoring.score(&w.to_string()));
+        sum
+    }
+}
+
+impl Round {
+    pub fn new(board: Board) -> Self {
+        Round {
+            board,
+            words: Vec::new(),
+        }
+    }
+
+    pub fn result(&self, dict: &Dict) -> RoundResult {
+        self.result_filtered(&FilteredDict::new(dict, &Dict::new()))
+    }
+
+    /// Checks the submitted words against a filtered dictionary. Blocked words that may not be
+    /// shown are left out of the missed words.
+    pub fn result_filtered(&self, dict: &FilteredDict) -> RoundResult {
+        let mut checker = Checker::new(&self.board, dict);
+        for word in self.words.iter() {
+            checker.check(word);
+        }
+        checker.finish()
+    }
+}
+
+/// Cancels the words found by more than one player on the same board, moving them from each
+/// player's correct words to their cancelled words, as in the standard rules.
+pub fn cancel_duplicates(results: &mut [RoundResult]) {
+    let mut found: Dict<usize> = Dict::default();
+    for result in results.iter() {
+        for word in result.correct.iter().flat_map(|w| w.parse::<BString>()) {
+            match found.get_mut(&word) {
+                Some(players) => *players += 1,
+                None => {
+                    found.insert_value(&word, 1);
+                }
+            }
+        }
+    }
+    let duplicate = |w: &String| {
+        w.parse::<BString>()
+            .is_ok_and(|w| found.get(&w).is_some_and(|&players| players > 1))
+    };
+    for result in results.iter_mut() {
+        let (cancelled, correct) = result.correct.drain(..).partition(duplicate);
+        result.correct = correct;
+        result.cancelled = cancelled;
+    }
+}
+
+/// The category a submitted word falls into, matching the fields of [`RoundResult`].
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    Correct,
+    NotPresent,
+    TooShort,
+    NotWord,
+    NotBword,
+    Blocked,
+}
+
+/// Checks words one at a time as they are submitted, for giving feedback during a round.
+#[derive(Debug, Clone)]
+pub struct Checker<'a> {
+    dict: &'a FilteredDict<'a>,
+    present: Dict,
+    result: RoundResult,
+}
+
+impl<'a> Checker<'a> {
+    pub fn new(board: &Board, dict: &'a FilteredDict<'a>) -> Self {
+        Checker {
+            dict,
+            present: dict.playable_words(board),
+            result: RoundResult::default(),
+        }
+    }
+
+    pub fn check(&mut self, word: &str) -> Verdict {
+        let (verdict, list) = match word.parse::<BString>() {
+            Ok(bword) => {
+                if self.present.remove(&bword) {
+                    (Verdict::Correct, &mut self.result.correct)
+                } else if self.dict.accepts(&bword) {
+                    (Verdict::NotPresent, &mut self.result.not_present)
+                } else if self.dict.blocks(&bword) {
+                    (Verdict::Blocked, &mut self.result.blocked)
+                } else if word.len() < self.dict.min_len {
+                    (Verdict::TooShort, &mut self.result.too_short)
+                } else {
+                    (Verdict::NotWord, &mut self.result.not_word)
+                }
+            }
+            Err(_) => (Verdict::NotBword, &mut self.result.not_bword),
+        };
+        list.push(String::from(word));
+        verdict
+    }
+
+    /// Checks a word entered at `now`, or returns `None` without recording it if `timer` had run
+    /// out, since words finished after the buzzer don't count.
+    pub fn check_at(&mut self, word: &str, timer: &Timer, now: Instant) -> Option<Verdict> {
+        if timer.is_up_at(now) {
+            return None;
+        }
+        Some(self.check(word))
+    }
+
+    /// The result so far, without the missed words.
+    pub fn result(&self) -> &RoundResult {
+        &self.result
+    }
+
+    /// The result so far, with the words not found yet as the missed words.
+    pub fn finish(self) -> RoundResult {
+        RoundResult {
+            missed: self.dict.hide(self.present),
+            ..self.result
+        }
+    }
+}
+
+/// The time limit of a round.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timer {
+    deadline: Instant,
+}
+
+impl Timer {
+    /// Starts a timer running for `length` from now.
+    pub fn new(length: Duration) -> Self {
+        Timer::starting_at(Instant::now(), length)
+    }
+
+    pub fn starting_at(start: Instant, length: Duration) -> Self {
+        Timer {
+            deadline: start + length,
+        }
+    }
+
+    pub fn deadline(&self) -> Instant {
+        self.deadline
+    }
+
+    /// The time left at `now`, which is zero once time is up.
+    pub fn left_at(&self, now: Instant) -> Duration {
+        self.deadline.saturating_duration_since(now)
+    }
+
+    pub fn is_up_at(&self, now: Instant) -> bool {
+        now >= self.deadline
+    }
+}
+
+/// Formats a time as minutes and seconds, rounding up so that the display never shows 0:00 while
+/// there is still time left.
+pub fn format_time(time: Duration) -> String {
+    let secs = time.as_secs() + (time.subsec_nanos() > 0) as u64;
+    format!("{}:{:02}", secs / 60, secs % 60)
+}
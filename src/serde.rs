@@ -1,16 +1,47 @@
-use serde::de::{Error, SeqAccess, Visitor};
-use serde::ser::SerializeSeq;
+use crate::{BChar, BStr, BString, Board, Dice, DiceSet, Dict};
+use enum_map::Enum;
+use serde::de::{Error, IgnoredAny, MapAccess, SeqAccess, Unexpected, Visitor};
+use serde::ser::{SerializeMap, SerializeSeq};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
-use crate::{BStr, BString, Dict};
 use std::fmt;
+use std::mem;
+use std::slice;
 use std::str::FromStr;
 
+// Human-readable formats store words as text. Binary formats store one byte per letter, holding
+// its `BChar` discriminant, which lets a `&BStr` borrow directly from the input. Before 0.2 binary
+// formats stored a sequence of `BChar` variants; self-describing formats can still read those.
+
+const _: () = assert!(mem::size_of::<BChar>() == 1);
+
+fn bchars_from_bytes(v: &[u8]) -> Option<&[BChar]> {
+    if v.iter().all(|&b| (b as usize) < BChar::LENGTH) {
+        // SAFETY: BChar is repr(u8) with discriminants 0 to LENGTH - 1, as the tests check, so
+        // every byte is a valid BChar of the same size and alignment
+        Some(unsafe { slice::from_raw_parts(v.as_ptr().cast::<BChar>(), v.len()) })
+    } else {
+        None
+    }
+}
+
+fn serialize_bstr<S>(s: &BStr, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    if serializer.is_human_readable() {
+        serializer.serialize_str(&s.to_string())
+    } else {
+        let bytes: Vec<u8> = s.0.iter().map(|&c| c as u8).collect();
+        serializer.serialize_bytes(&bytes)
+    }
+}
+
 impl Serialize for BString {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
     {
-        serializer.serialize_str(&self.to_string())
+        serialize_bstr(self, serializer)
     }
 }
 
@@ -29,6 +60,25 @@ impl<'de> Visitor<'de> for BStringVisitor {
     {
         BString::from_str(v).map_err(|e| Error::custom(e.to_string()))
     }
+
+    fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E>
+    where
+        E: Error,
+    {
+        bchars_from_bytes(v)
+            .map(|s| BStr::from_slice(s).to_owned())
+            .ok_or_else(|| Error::invalid_value(Unexpected::Bytes(v), &self))
+    }
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut chars = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(c) = seq.next_element::<BChar>()? {
+            chars.push(c);
+        }
+        Ok(BStr::from_slice(&chars).to_owned())
+    }
 }
 
 impl<'de> Deserialize<'de> for BString {
@@ -36,7 +86,11 @@ impl<'de> Deserialize<'de> for BString {
     where
         D: Deserializer<'de>,
     {
-        deserializer.deserialize_str(BStringVisitor)
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_str(BStringVisitor)
+        } else {
+            deserializer.deserialize_bytes(BStringVisitor)
+        }
     }
 }
 
@@ -45,7 +99,42 @@ impl Serialize for BStr {
     where
         S: Serializer,
     {
-        serializer.serialize_str(&self.to_string())
+        serialize_bstr(self, serializer)
+    }
+}
+
+struct BorrowedBStrVisitor;
+
+impl<'de> Visitor<'de> for BorrowedBStrVisitor {
+    type Value = &'de BStr;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a borrowed boggle string in a binary format")
+    }
+
+    fn visit_borrowed_bytes<E>(self, v: &'de [u8]) -> Result<Self::Value, E>
+    where
+        E: Error,
+    {
+        bchars_from_bytes(v)
+            .map(BStr::from_slice)
+            .ok_or_else(|| Error::invalid_value(Unexpected::Bytes(v), &self))
+    }
+}
+
+/// Only binary formats can lend a `&BStr`; human-readable formats store words as text, so
+/// deserialize a [`BString`] from those instead.
+impl<'de: 'a, 'a> Deserialize<'de> for &'a BStr {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        if deserializer.is_human_readable() {
+            return Err(D::Error::custom(
+                "a borrowed boggle string cannot be deserialized from a human-readable format",
+            ));
+        }
+        deserializer.deserialize_bytes(BorrowedBStrVisitor)
     }
 }
 
@@ -54,7 +143,7 @@ impl Serialize for Dict {
     where
         S: Serializer,
     {
-        let mut seq = serializer.serialize_seq(None)?;
+        let mut seq = serializer.serialize_seq(Some(self.len()))?;
         self.try_traverse(|s| seq.serialize_element(&s))?;
         seq.end()
     }
@@ -90,3 +179,247 @@ impl<'de> Deserialize<'de> for Dict {
     }
 }
 
+fn parse_faces<E: Error>(s: &str, len: usize) -> Result<Vec<BChar>, E> {
+    let faces = BString::from_str(s)
+        .map_err(|e| E::custom(e.to_string()))?
+        .to_vec();
+    if faces.len() != len {
+        return Err(E::invalid_length(
+            faces.len(),
+            &&*format!("{} letters", len),
+        ));
+    }
+    Ok(faces)
+}
+
+fn faces_to_string(faces: &[BChar]) -> String {
+    faces.iter().map(|c| c.to_string()).collect()
+}
+
+impl Serialize for Board {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut seq = serializer.serialize_seq(Some(4))?;
+        for row in self.0.iter() {
+            seq.serialize_element(&faces_to_string(row))?;
+        }
+        seq.end()
+    }
+}
+
+struct BoardVisitor;
+
+impl<'de> Visitor<'de> for BoardVisitor {
+    type Value = Board;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("four rows of four letters")
+    }
+
+    fn visit_seq<S>(self, mut access: S) -> Result<Self::Value, S::Error>
+    where
+        S: SeqAccess<'de>,
+    {
+        let mut board = Board::default();
+        for (i, row) in board.0.iter_mut().enumerate() {
+            let s = access
+                .next_element::<String>()?
+                .ok_or_else(|| S::Error::invalid_length(i, &self))?;
+            row.copy_from_slice(&parse_faces::<S::Error>(&s, 4)?);
+        }
+        if access.next_element::<IgnoredAny>()?.is_some() {
+            return Err(S::Error::invalid_length(5, &self));
+        }
+        Ok(board)
+    }
+}
+
+impl<'de> Deserialize<'de> for Board {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_seq(BoardVisitor)
+    }
+}
+
+/// Serializes a [`Dice`] as a list of strings, one per die, each holding that die's six faces.
+///
+/// Use with `#[serde(with = "boggle::serde::dice")]`.
+pub mod dice {
+    use super::*;
+
+    pub fn serialize<S>(dice: &Dice, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut seq = serializer.serialize_seq(Some(dice.len()))?;
+        for die in dice.iter() {
+            seq.serialize_element(&faces_to_string(die))?;
+        }
+        seq.end()
+    }
+
+    struct DiceVisitor;
+
+    impl<'de> Visitor<'de> for DiceVisitor {
+        type Value = Dice;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("sixteen dice of six faces")
+        }
+
+        fn visit_seq<S>(self, mut access: S) -> Result<Self::Value, S::Error>
+        where
+            S: SeqAccess<'de>,
+        {
+            let mut dice = Dice::default();
+            for (i, die) in dice.iter_mut().enumerate() {
+                let s = access
+                    .next_element::<String>()?
+                    .ok_or_else(|| S::Error::invalid_length(i, &self))?;
+                die.copy_from_slice(&parse_faces::<S::Error>(&s, 6)?);
+            }
+            if access.next_element::<IgnoredAny>()?.is_some() {
+                return Err(S::Error::invalid_length(17, &self));
+            }
+            Ok(dice)
+        }
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Dice, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_seq(DiceVisitor)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "DiceSet")]
+struct DiceSetRepr {
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(with = "dice")]
+    dice: Dice,
+}
+
+impl Serialize for DiceSet {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        DiceSetRepr {
+            name: self.name.clone(),
+            dice: self.dice,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for DiceSet {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let DiceSetRepr { name, dice } = DiceSetRepr::deserialize(deserializer)?;
+        Ok(DiceSet { name, dice })
+    }
+}
+
+/// Serializes a [`Dict`] as nested maps following the structure of the trie, rather than as a
+/// flat list of words. Each node maps the letters that can follow it to their subtries, with the
+/// key `"$"` marking the end of a word. This shares common prefixes, so is usually more compact.
+///
+/// Use with `#[serde(with = "boggle::serde::trie")]`.
+pub mod trie {
+    use super::*;
+
+    struct Node<'a>(&'a Dict);
+
+    impl<'a> Serialize for Node<'a> {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            let children = self.0.children.iter().filter(|(_, v)| v.is_some()).count();
+            let len = children + self.0.val.is_some() as usize;
+            let mut map = serializer.serialize_map(Some(len))?;
+            if self.0.val.is_some() {
+                map.serialize_entry("$", &())?;
+            }
+            for (c, v) in self.0.children.iter() {
+                if let Some(d) = v {
+                    map.serialize_entry(&BStr::from_slice(&[c]).to_string(), &Node(d))?;
+                }
+            }
+            map.end()
+        }
+    }
+
+    pub fn serialize<S>(dict: &Dict, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        Node(dict).serialize(serializer)
+    }
+
+    struct NodeBuf(Dict);
+
+    struct NodeVisitor;
+
+    impl<'de> Visitor<'de> for NodeVisitor {
+        type Value = NodeBuf;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("a map of letters to subtries")
+        }
+
+        fn visit_map<M>(self, mut access: M) -> Result<Self::Value, M::Error>
+        where
+            M: MapAccess<'de>,
+        {
+            let mut dict = Dict::new();
+            while let Some(key) = access.next_key::<String>()? {
+                if key == "$" {
+                    access.next_value::<()>()?;
+                    if dict.val.replace(()).is_none() {
+                        dict.len += 1;
+                    }
+                    continue;
+                }
+                let c = match BString::from_str(&key).map(BString::to_vec).as_deref() {
+                    Ok(&[c]) => c,
+                    _ => return Err(M::Error::invalid_value(Unexpected::Str(&key), &self)),
+                };
+                let NodeBuf(child) = access.next_value()?;
+                if let Some(old) = dict.children[c].take() {
+                    dict.len -= old.len;
+                }
+                if !child.is_empty() {
+                    dict.len += child.len;
+                    dict.children[c] = Some(Box::new(child));
+                }
+            }
+            Ok(NodeBuf(dict))
+        }
+    }
+
+    impl<'de> Deserialize<'de> for NodeBuf {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            deserializer.deserialize_map(NodeVisitor)
+        }
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Dict, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        NodeBuf::deserialize(deserializer).map(|NodeBuf(dict)| dict)
+    }
+}
@@ -2,6 +2,10 @@ use crate::*;
 use rand::SeedableRng;
 use rand_pcg::Pcg32;
 
+fn dict(words: &[&str]) -> Dict {
+    words.iter().flat_map(|s| s.parse::<BString>()).collect()
+}
+
 fn board_from_u64(seed: u64) -> Board {
     let mut rng = Pcg32::seed_from_u64(seed);
     let dice = [
@@ -28,19 +32,16 @@ fn board_from_u64(seed: u64) -> Board {
 #[test]
 fn test_roll() {
     let board = board_from_u64(7);
-    let exp = Board([
-        [R, L, T, T],
-        [E, F, O, E],
-        [M, P, I, T],
-        [E, H, V, L]
-    ]);
+    let exp = Board([[R, L, T, T], [E, F, O, E], [M, P, I, T], [E, H, V, L]]);
     assert_eq!(board.0, exp.0);
 }
 
 #[test]
 fn find_words() {
     let board = board_from_u64(7);
-    let dict = ["ref", "remep", "world", "pit", "pity", "toe", "vile", "ferler"].into_iter().flat_map(|s| s.parse::<BString>()).collect();
+    let dict = dict(&[
+        "ref", "remep", "world", "pit", "pity", "toe", "vile", "ferler",
+    ]);
     let words = board.words_trie(&dict);
     assert!(words.contains(&"ref".parse::<BString>().unwrap()));
     assert!(words.contains(&"remep".parse::<BString>().unwrap()));
@@ -52,3 +53,938 @@ fn find_words() {
     assert!(!words.contains(&"ferler".parse::<BString>().unwrap()));
     assert!(!words.contains(&"hello".parse::<BString>().unwrap()));
 }
+
+#[test]
+fn parse_legacy_dice() {
+    let set = io::parse_dice_set(include_str!("../dice.txt")).unwrap();
+    assert_eq!(set.name, None);
+    assert_eq!(set.dice[0], [A, B, B, O, O, J]);
+    assert_eq!(set.dice[3], [M, U, Qu, H, I, N]);
+}
+
+#[test]
+fn parse_dice_with_header() {
+    let mut text = String::from("# classic dice\nname: Classic\nsize: 4x4\n\n");
+    for line in include_str!("../dice.txt").lines() {
+        text.push_str(line);
+        text.push_str("  # a die\n");
+    }
+    let set = io::parse_dice_set(&text).unwrap();
+    assert_eq!(set.name.as_deref(), Some("Classic"));
+    assert_eq!(io::parse_dice_set(&set.to_string()).unwrap(), set);
+    assert!(io::parse_dice_set(&text.replace("4x4", "5x5")).is_err());
+    assert!(io::parse_dice_set("A B C D E\n").is_err());
+}
+
+#[test]
+fn write_dice_set() {
+    let mut set = io::parse_dice_set(include_str!("../dice.txt")).unwrap();
+    set.name = Some(String::from("Big #1\nC:\\dice"));
+    let text = set.to_string();
+    assert!(text.starts_with("name: Big \\#1\\nC:\\\\dice\n"));
+    assert_eq!(io::parse_dice_set(&text).unwrap(), set);
+    assert!(io::parse_dice_set("name: a\\q\n").is_err());
+
+    let path = std::env::temp_dir().join(format!("boggle-dice-{}", std::process::id()));
+    io::write_dice_set(&path, &set).unwrap();
+    assert_eq!(io::read_dice_set(&path).unwrap(), set);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn round_result() {
+    let board = board_from_u64(7);
+    let dict = dict(&["ref", "remep", "pit", "toe", "world"]);
+    let mut round = round::Round::new(board);
+    for w in ["ref", "REMEP", "ref", "world", "it", "xyzzy", "qat"].iter() {
+        round.words.push(String::from(*w));
+    }
+    let result = round.result(&dict);
+    assert_eq!(result.correct, vec!["ref", "REMEP"]);
+    assert_eq!(result.not_present, vec!["ref", "world"]);
+    assert_eq!(result.too_short, vec!["it"]);
+    assert_eq!(result.not_word, vec!["xyzzy"]);
+    assert_eq!(result.not_bword, vec!["qat"]);
+    assert_eq!(result.score(), 3);
+    assert_eq!(result.max_score(), 5);
+}
+
+#[cfg(feature = "serde")]
+mod serde_tests {
+    use super::{board_from_u64, dict};
+    use crate::*;
+    use ::serde::{Deserialize, Serialize};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Compact {
+        #[serde(with = "crate::serde::trie")]
+        dict: Dict,
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let board = board_from_u64(7);
+        let json = serde_json::to_string(&board).unwrap();
+        assert_eq!(json, r#"["RLTT","EFOE","MPIT","EHVL"]"#);
+        assert_eq!(serde_json::from_str::<Board>(&json).unwrap(), board);
+        assert!(serde_json::from_str::<Board>(r#"["RLTT","EFOE","MPIT"]"#).is_err());
+
+        let dict = dict(&["cat", "cats", "cot", "quit"]);
+        let json = serde_json::to_string(&dict).unwrap();
+        assert_eq!(json, r#"["cat","cats","cot","quit"]"#);
+        assert_eq!(serde_json::from_str::<Dict>(&json).unwrap(), dict);
+
+        let set = io::parse_dice_set(include_str!("../dice.txt")).unwrap();
+        let json = serde_json::to_string(&set).unwrap();
+        assert!(json.starts_with(r#"{"name":null,"dice":["ABBOOJ","DEYLRV","DEXLIR","MUQuHIN","#));
+        assert_eq!(serde_json::from_str::<DiceSet>(&json).unwrap(), set);
+
+        let mut round = round::Round::new(board);
+        round.words.push(String::from("ref"));
+        let json = serde_json::to_string(&round).unwrap();
+        assert_eq!(serde_json::from_str::<round::Round>(&json).unwrap(), round);
+
+        assert!(serde_json::from_str::<&BStr>(r#""cat""#).is_err());
+    }
+
+    #[test]
+    fn json_trie_round_trip() {
+        let compact = Compact {
+            dict: dict(&["cat", "cats", "cot", "quit"]),
+        };
+        let json = serde_json::to_string(&compact).unwrap();
+        assert_eq!(
+            json,
+            r#"{"dict":{"c":{"a":{"t":{"$":null,"s":{"$":null}}},"o":{"t":{"$":null}}},"qu":{"i":{"t":{"$":null}}}}}"#
+        );
+        assert_eq!(serde_json::from_str::<Compact>(&json).unwrap(), compact);
+    }
+
+    #[test]
+    fn bincode_round_trip() {
+        let dict = dict(&["cat", "cats", "cot", "quit"]);
+        let bytes = bincode::serialize(&dict).unwrap();
+        assert_eq!(bincode::deserialize::<Dict>(&bytes).unwrap(), dict);
+
+        let compact = Compact { dict: dict.clone() };
+        let bytes = bincode::serialize(&compact).unwrap();
+        assert_eq!(bincode::deserialize::<Compact>(&bytes).unwrap(), compact);
+
+        let word = "quit".parse::<BString>().unwrap();
+        let bytes = bincode::serialize(&word).unwrap();
+        assert_eq!(bincode::deserialize::<&BStr>(&bytes).unwrap(), &*word);
+        assert_eq!(bincode::deserialize::<BString>(&bytes).unwrap(), word);
+
+        let set = io::parse_dice_set(include_str!("../dice.txt")).unwrap();
+        let bytes = bincode::serialize(&set).unwrap();
+        assert_eq!(bincode::deserialize::<DiceSet>(&bytes).unwrap(), set);
+
+        // borrowing bytes as letters relies on the discriminants running from 0 to LENGTH - 1
+        assert_eq!(BChar::LENGTH, Z as usize + 1);
+        for i in 0..BChar::LENGTH {
+            assert_eq!(BChar::from_usize(i) as usize, i);
+        }
+        assert!(bincode::deserialize::<BString>(&[1, 0, 0, 0, 0, 0, 0, 0, 26]).is_err());
+    }
+
+    #[test]
+    fn old_bstring_format() {
+        use ::serde::de::value::{Error, SeqDeserializer};
+        let seq = SeqDeserializer::<_, Error>::new(vec!["Qu", "I", "T"].into_iter());
+        assert_eq!(
+            BString::deserialize(seq).unwrap(),
+            "quit".parse::<BString>().unwrap()
+        );
+    }
+}
+
+#[test]
+fn prefix_and_pattern_queries() {
+    let dict = dict(&["cat", "cats", "cot", "coating", "sing", "quit", "quiet"]);
+    let bstr = |s: &str| s.parse::<BString>().unwrap();
+    assert!(dict.has_prefix(&bstr("co")));
+    assert!(!dict.has_prefix(&bstr("cu")));
+    assert_eq!(
+        dict.subtrie(&bstr("cat")).unwrap().words(),
+        vec![bstr(""), bstr("s")]
+    );
+    assert_eq!(
+        dict.words_with_prefix(&bstr("qui")),
+        vec![bstr("quiet"), bstr("quit")]
+    );
+    assert_eq!(
+        dict.words_with_len(4..=4),
+        vec![bstr("cats"), bstr("quiet"), bstr("sing")]
+    );
+    assert_eq!(
+        dict.words_with_len(..4),
+        vec![bstr("cat"), bstr("cot"), bstr("quit")]
+    );
+
+    let words = |p: &str| dict.matching(&p.parse().unwrap()).words();
+    assert_eq!(words("C?T"), vec![bstr("cat"), bstr("cot")]);
+    assert_eq!(words("*ING"), vec![bstr("coating"), bstr("sing")]);
+    assert_eq!(
+        words("*T*"),
+        vec![
+            bstr("cat"),
+            bstr("cats"),
+            bstr("coating"),
+            bstr("cot"),
+            bstr("quiet"),
+            bstr("quit")
+        ]
+    );
+    assert_eq!(words("?"), Vec::<BString>::new());
+    assert_eq!(
+        "C**T".parse::<Pattern>().unwrap(),
+        "C*T".parse::<Pattern>().unwrap()
+    );
+    assert_eq!(words("*******ING"), words("*ING"));
+    assert!("Q?".parse::<Pattern>().is_err());
+}
+
+#[test]
+fn dict_iter() {
+    let mut dict = dict(&["cat", "a", "cats", "quit", "zoo", "cot"]);
+    assert_eq!(dict.len(), 6);
+    assert!(!dict.insert(&"cat".parse::<BString>().unwrap()));
+    assert!(dict.remove(&"zoo".parse::<BString>().unwrap()));
+    assert!(!dict.remove(&"zoo".parse::<BString>().unwrap()));
+    assert_eq!(dict.len(), 5);
+
+    let words = dict.words();
+    assert_eq!(dict.iter().collect::<Vec<_>>(), words);
+    assert_eq!(
+        dict.iter().rev().collect::<Vec<_>>(),
+        words.iter().rev().cloned().collect::<Vec<_>>()
+    );
+    let mut iter = dict.iter();
+    assert_eq!(iter.len(), 5);
+    assert_eq!(iter.next(), Some(words[0].clone()));
+    assert_eq!(iter.next_back(), Some(words[4].clone()));
+    assert_eq!(iter.next_back(), Some(words[3].clone()));
+    assert_eq!(iter.next(), Some(words[1].clone()));
+    assert_eq!(iter.next(), Some(words[2].clone()));
+    assert_eq!(iter.next(), None);
+    assert_eq!(iter.next_back(), None);
+
+    let mut into_iter = dict.clone().into_iter();
+    assert_eq!(into_iter.next_back(), Some(words[4].clone()));
+    assert_eq!(into_iter.collect::<Vec<_>>(), &words[..4]);
+    assert_eq!((&dict).into_iter().count(), 5);
+    assert_eq!(Dict::new().iter().next(), None);
+}
+
+#[test]
+fn dict_set_operations() {
+    let a = dict(&["cat", "cats", "cot", "dog"]);
+    let b = dict(&["cat", "cot", "cotton", "emu"]);
+    assert_eq!(
+        &a | &b,
+        dict(&["cat", "cats", "cot", "cotton", "dog", "emu"])
+    );
+    assert_eq!(&a & &b, dict(&["cat", "cot"]));
+    assert_eq!(&a - &b, dict(&["cats", "dog"]));
+    assert_eq!(&a ^ &b, dict(&["cats", "cotton", "dog", "emu"]));
+    assert_eq!((&a ^ &b).len(), 4);
+    assert_eq!(&a & &dict(&["ca", "do"]), Dict::new());
+
+    let mut c = a.clone();
+    c -= &a;
+    assert!(c.is_empty());
+    c |= &b;
+    c &= &a;
+    assert_eq!(c, &b & &a);
+}
+
+#[test]
+fn filtered_round() {
+    let board = board_from_u64(7);
+    let allow = dict(&["ref", "remep", "pit", "toe"]);
+    let block = dict(&["pit", "toe"]);
+    let mut filter = filter::FilteredDict::new(&allow, &block);
+    let mut round = round::Round::new(board);
+    round.words.push(String::from("pit"));
+
+    let result = round.result_filtered(&filter);
+    assert_eq!(result.correct, vec!["pit"]);
+    assert_eq!(
+        result.missed.words(),
+        vec!["ref".parse::<BString>().unwrap(), "remep".parse().unwrap()]
+    );
+    assert!(!filter.shows(&"toe".parse::<BString>().unwrap()));
+
+    filter.play = filter::BlockPolicy::Block;
+    let result = round.result_filtered(&filter);
+    assert_eq!(result.blocked, vec!["pit"]);
+    assert!(result.not_word.is_empty());
+    assert_eq!(filter.playable_words(&board).len(), 2);
+}
+
+#[test]
+fn valued_dict() {
+    let bstr = |s: &str| s.parse::<BString>().unwrap();
+    let mut freqs: Dict<u64> = vec![(bstr("cat"), 50), (bstr("cats"), 7), (bstr("quit"), 12)]
+        .into_iter()
+        .collect();
+    assert_eq!(freqs.get(&bstr("cats")), Some(&7));
+    assert_eq!(freqs.insert_value(&bstr("cat"), 60), Some(50));
+    *freqs.get_mut(&bstr("quit")).unwrap() += 1;
+    assert_eq!(
+        freqs.entries().map(|(w, &f)| (w, f)).collect::<Vec<_>>(),
+        vec![(bstr("cat"), 60), (bstr("cats"), 7), (bstr("quit"), 13)]
+    );
+
+    let blocked: Dict = [bstr("cats")].iter().map(|w| &**w).collect();
+    assert_eq!((&freqs - &blocked).len(), 2);
+    assert_eq!(Board::default().words_trie(&freqs), Dict::new());
+
+    let path = std::env::temp_dir().join(format!("boggle-freq-{}.txt", std::process::id()));
+    io::write_freq_dict(&path, &freqs).unwrap();
+    let read = io::read_freq_dict(&path);
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(read.unwrap(), freqs);
+    assert_eq!(freqs.remove_value(&bstr("cat")), Some(60));
+    assert_eq!(freqs.pop_last_entry(), Some((bstr("quit"), 13)));
+}
+
+#[test]
+fn read_definitions() {
+    let path = std::env::temp_dir().join(format!("boggle-defs-{}.txt", std::process::id()));
+    std::fs::write(
+        &path,
+        "cat\ta small feline\nqat\tnot playable\ncat\tto vomit\n\nquit\tto leave\n",
+    )
+    .unwrap();
+    let defs = io::read_definitions(&path);
+    std::fs::remove_file(&path).unwrap();
+    let defs = defs.unwrap();
+    assert_eq!(defs.len(), 2);
+    assert_eq!(
+        defs.get(&"CAT".parse::<BString>().unwrap())
+            .map(String::as_str),
+        Some("a small feline; to vomit")
+    );
+    assert_eq!(
+        defs.get(&"quit".parse::<BString>().unwrap())
+            .map(String::as_str),
+        Some("to leave")
+    );
+
+    let mut defs = Definitions::default();
+    let def = "1. to leave\n2. to stop\tfor good, C:\\";
+    defs.insert_value(&"quit".parse::<BString>().unwrap(), String::from(def));
+    io::write_definitions(&path, &defs).unwrap();
+    let read = io::read_definitions(&path);
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(read.unwrap(), defs);
+}
+
+#[test]
+fn anagrams() {
+    let dict = dict(&["act", "cat", "cats", "scat", "at", "taco", "coat", "tact"]);
+    let letters = "tacs".parse::<BString>().unwrap();
+    let words = |d: Dict| d.words().iter().map(|w| w.to_string()).collect::<Vec<_>>();
+    assert_eq!(words(dict.anagrams(&letters)), vec!["cats", "scat"]);
+    assert_eq!(
+        words(dict.sub_anagrams(&letters, 3)),
+        vec!["act", "cat", "cats", "scat"]
+    );
+    assert_eq!(
+        words(dict.sub_anagrams(&letters, 0)),
+        vec!["act", "at", "cat", "cats", "scat"]
+    );
+    assert!(dict
+        .anagrams(&"quit".parse::<BString>().unwrap())
+        .is_empty());
+    // more of one letter than fits in a byte
+    let letters = format!("{}ct", "a".repeat(300)).parse::<BString>().unwrap();
+    assert_eq!(
+        words(dict.sub_anagrams(&letters, 0)),
+        vec!["act", "at", "cat"]
+    );
+}
+
+#[test]
+fn fuzzy_lookup() {
+    let words = dict(&["cat", "cart", "coat", "cot", "dog", "quit", "quiet"]);
+    let found = |w: &str, d| {
+        words
+            .fuzzy(&w.parse::<BString>().unwrap(), d)
+            .into_iter()
+            .map(|(w, d)| (w.to_string(), d))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(found("cat", 0), vec![("cat".to_string(), 0)]);
+    assert_eq!(
+        found("caat", 1),
+        vec![
+            ("cart".to_string(), 1),
+            ("cat".to_string(), 1),
+            ("coat".to_string(), 1)
+        ]
+    );
+    assert_eq!(found("quiy", 1), vec![("quit".to_string(), 1)]);
+    assert_eq!(found("xyz", 2), vec![]);
+
+    let board = board_from_u64(7);
+    let on_board = board.words_trie(&dict(&["pit", "toe", "tip"]));
+    assert_eq!(
+        on_board.fuzzy(&"pot".parse::<BString>().unwrap(), 1).len(),
+        1
+    );
+}
+
+#[test]
+fn hints() {
+    let board = board_from_u64(7);
+    let found = dict(&["ref", "pit"]);
+    let words = dict(&["ref", "remep", "pit", "toe"]);
+    let hints = hint::Hints::new(board, &board.words_trie(&words), &found);
+    let mut rng = Pcg32::seed_from_u64(1);
+    assert_eq!(
+        hints.counts(),
+        hint::Hint::Counts(vec![(3, 1), (5, 1)].into_iter().collect())
+    );
+    match hints.first_letters(2, &mut rng) {
+        Some(hint::Hint::FirstLetters(letters, len)) => {
+            assert!(["re", "to"].contains(&&*letters.to_string()));
+            assert!(len == 3 || len == 5);
+        }
+        hint => panic!("unexpected hint {:?}", hint),
+    }
+    match hints.starting_tile(&mut rng) {
+        Some(hint::Hint::StartingTile(row, col)) => {
+            assert!(board[row][col] == R || board[row][col] == T)
+        }
+        hint => panic!("unexpected hint {:?}", hint),
+    }
+    assert_eq!(
+        board.find_path(&"remep".parse::<BString>().unwrap()),
+        Some(vec![(0, 0), (1, 0), (2, 0), (3, 0), (2, 1)])
+    );
+    assert_eq!(board.find_path(&"pity".parse::<BString>().unwrap()), None);
+
+    let board = Board([[Qu, I, T, S], [E, E, E, E], [E, E, E, E], [E, E, E, E]]);
+    let words = dict(&["quit"]);
+    let hints = hint::Hints::new(board, &board.words_trie(&words), &Dict::new());
+    assert_eq!(
+        hints.counts(),
+        hint::Hint::Counts(vec![(4, 1)].into_iter().collect())
+    );
+    assert_eq!(
+        hints.first_letters(1, &mut rng),
+        Some(hint::Hint::FirstLetters("qu".parse().unwrap(), 4))
+    );
+}
+
+#[test]
+fn timed_checker() {
+    use std::time::{Duration, Instant};
+
+    assert_eq!(round::format_time(Duration::ZERO), "0:00");
+    assert_eq!(round::format_time(Duration::from_millis(100)), "0:01");
+    assert_eq!(round::format_time(Duration::from_millis(59_001)), "1:00");
+    assert_eq!(round::format_time(Duration::from_secs(180)), "3:00");
+
+    let board = board_from_u64(7);
+    let dict = dict(&["ref", "remep", "pit", "toe"]);
+    let block = Dict::new();
+    let filter = filter::FilteredDict::new(&dict, &block);
+    let start = Instant::now();
+    let timer = round::Timer::starting_at(start, Duration::from_secs(180));
+    assert_eq!(timer.left_at(start), Duration::from_secs(180));
+    assert_eq!(
+        timer.left_at(start + Duration::from_secs(150)),
+        Duration::from_secs(30)
+    );
+    assert!(!timer.is_up_at(start + Duration::from_millis(179_999)));
+    assert!(timer.is_up_at(start + Duration::from_secs(180)));
+    assert_eq!(
+        timer.left_at(start + Duration::from_secs(200)),
+        Duration::ZERO
+    );
+
+    let mut checker = round::Checker::new(&board, &filter);
+    let on_time = start + Duration::from_secs(10);
+    assert_eq!(
+        checker.check_at("ref", &timer, on_time),
+        Some(round::Verdict::Correct)
+    );
+    assert_eq!(
+        checker.check_at("ref", &timer, on_time),
+        Some(round::Verdict::NotPresent)
+    );
+    assert_eq!(
+        checker.check_at("it", &timer, on_time),
+        Some(round::Verdict::TooShort)
+    );
+    assert_eq!(
+        checker.check_at("xyzzy", &timer, on_time),
+        Some(round::Verdict::NotWord)
+    );
+    assert_eq!(checker.check_at("pit", &timer, timer.deadline()), None);
+    let result = checker.finish();
+    assert_eq!(result.correct, vec!["ref"]);
+    assert_eq!(result.not_present, vec!["ref"]);
+    assert!(result.missed.contains(&"pit".parse::<BString>().unwrap()));
+    assert_eq!(result.max_score(), 5);
+}
+
+#[test]
+fn scoring_and_min_len() {
+    let board = board_from_u64(7);
+    let allow = dict(&["ref", "remep", "pit", "toe"]);
+    let block = Dict::new();
+    let mut filter = filter::FilteredDict::new(&allow, &block);
+    filter.min_len = 4;
+    let mut round = round::Round::new(board);
+    round
+        .words
+        .extend(["pit", "remep"].iter().map(|w| String::from(*w)));
+
+    let result = round.result_filtered(&filter);
+    assert_eq!(result.correct, vec!["remep"]);
+    assert_eq!(result.too_short, vec!["pit"]);
+    assert!(result.missed.is_empty());
+    assert_eq!(result.score_with(Scoring::Letters), 5);
+
+    assert_eq!("big".parse(), Ok(Scoring::Big));
+    assert_eq!(Scoring::Big.score("cat"), 0);
+    assert_eq!(Scoring::Big.score("cats"), 1);
+    assert_eq!(Scoring::Classic.score("cats"), 1);
+    assert_eq!(Scoring::Classic.score("quilts"), 3);
+}
+
+#[test]
+fn parse_board() {
+    let board = board_from_u64(7);
+    assert_eq!(board.to_string().parse(), Ok(board));
+    assert_eq!("rltt efoe mpit ehvl".parse(), Ok(board));
+    assert!("RLTT EFOE MPIT".parse::<Board>().is_err());
+    assert!("RLTT EFOE MPIT EHV".parse::<Board>().is_err());
+    assert!("RL TT EFOE MPIT EHVL".parse::<Board>().is_err());
+}
+
+#[test]
+fn layered_dict() {
+    let board = board_from_u64(7);
+    let bstr = |s: &str| s.parse::<BString>().unwrap();
+    let base = dict(&["ref", "pit", "toe", "cat"]);
+    let mut dict = layered::LayeredDict::new(&base);
+    assert!(dict.insert(&bstr("remep")));
+    assert!(!dict.insert(&bstr("ref")));
+    assert!(dict.remove(&bstr("pit")));
+    assert!(dict.remove(&bstr("cat")));
+    assert!(dict.insert(&bstr("cat")));
+    assert!(!dict.contains(&bstr("pit")));
+    assert_eq!(dict.len(), 4);
+    assert_eq!(dict.added().words(), vec![bstr("remep")]);
+    assert_eq!(dict.removed().words(), vec![bstr("pit")]);
+    assert_eq!(
+        dict.words_trie(&board).words(),
+        vec![bstr("ref"), bstr("remep"), bstr("toe")]
+    );
+    assert_eq!(dict.merged().len(), 4);
+
+    let dir = std::env::temp_dir().join(format!("boggle-layered-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let (added, removed) = (dir.join("added.txt"), dir.join("removed.txt"));
+    assert!(io::read_overlay(&base, &added, &removed)
+        .unwrap()
+        .added()
+        .is_empty());
+    io::write_overlay(&dict, &added, &removed).unwrap();
+    let read = io::read_overlay(&base, &added, &removed).unwrap();
+    assert_eq!(read.merged(), dict.merged());
+
+    // custom words that the base has too are kept, in case it changes again
+    let mut bigger = base.clone();
+    bigger.insert(&bstr("remep"));
+    let mut read = io::read_overlay(&bigger, &added, &removed).unwrap();
+    assert!(read.added().is_empty());
+    assert!(read.insert(&bstr("tot")));
+    io::write_overlay(&read, &added, &removed).unwrap();
+    let read = io::read_overlay(&base, &added, &removed).unwrap();
+    assert_eq!(read.added().words(), vec![bstr("remep"), bstr("tot")]);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn path_matcher() {
+    // RLTT
+    // EFOE
+    // MPIT
+    // EHVL
+    let board = board_from_u64(7);
+    let dict = dict(&["tot", "toe", "tote", "pit"]);
+    let mut matcher = matcher::PathMatcher::new(&board, &dict);
+    assert!(matcher.is_on_board() && matcher.extends());
+    assert!(matcher.push(T));
+    assert_eq!(matcher.paths().len(), 3);
+    assert!(matcher.push(O));
+    assert_eq!(
+        matcher.paths(),
+        &[
+            vec![(0, 2), (1, 2)],
+            vec![(0, 3), (1, 2)],
+            vec![(2, 3), (1, 2)]
+        ]
+    );
+    assert!(matcher.extends() && !matcher.is_word());
+    assert!(matcher.push(E));
+    assert!(matcher.is_word());
+    assert_eq!(matcher.paths().len(), 3);
+    assert!(!matcher.push(E));
+    assert!(!matcher.is_on_board() && !matcher.extends());
+    assert_eq!(matcher.pop(), Some(E));
+
+    assert!(matcher.set(&"tot".parse::<BString>().unwrap()));
+    assert!(matcher.is_word());
+    let positions = matcher.positions();
+    assert!(positions[0][2] && positions[0][3] && positions[2][3] && positions[1][2]);
+    assert!(!positions[2][2]);
+    matcher.clear();
+    assert!(matcher.prefix().is_empty());
+}
+
+#[test]
+fn cancel_duplicates() {
+    let board = board_from_u64(7);
+    let dict = dict(&["ref", "remep", "pit", "toe", "world"]);
+    let mut round = round::Round::new(board);
+    round.words.push(String::from("ref"));
+    round.words.push(String::from("REMEP"));
+    let mut other = round::Round::new(board);
+    other.words.push(String::from("ref"));
+    other.words.push(String::from("toe"));
+    let mut results = [round.result(&dict), other.result(&dict)];
+    round::cancel_duplicates(&mut results);
+    assert_eq!(results[0].correct, vec!["REMEP"]);
+    assert_eq!(results[0].cancelled, vec!["ref"]);
+    assert_eq!(results[1].correct, vec!["toe"]);
+    assert_eq!(results[0].score(), 2);
+    assert_eq!(results[0].max_score(), 5);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn protocol_messages() {
+    let message: protocol::ClientMessage =
+        serde_json::from_str(r#"{"type":"join","room":"lobby","name":"ann"}"#).unwrap();
+    assert_eq!(
+        message,
+        protocol::ClientMessage::Join {
+            room: String::from("lobby"),
+            name: String::from("ann")
+        }
+    );
+    let message = protocol::ServerMessage::Verdict {
+        word: String::from("ref"),
+        verdict: round::Verdict::NotWord,
+    };
+    assert_eq!(
+        serde_json::to_string(&message).unwrap(),
+        r#"{"type":"verdict","word":"ref","verdict":"not_word"}"#
+    );
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn room_rounds() {
+    use protocol::ServerMessage;
+    use std::collections::BTreeMap;
+    use std::sync::mpsc::{channel, Receiver};
+    use std::time::{Duration, Instant};
+
+    let messages = |rx: &Receiver<ServerMessage>| rx.try_iter().collect::<Vec<_>>();
+    let players = |names: &[&str]| ServerMessage::Players {
+        room: String::from("lobby"),
+        players: names.iter().map(|name| name.to_string()).collect(),
+    };
+    let progress = |found: &[(&str, usize)]| ServerMessage::Progress {
+        found: found
+            .iter()
+            .map(|&(name, n)| (name.to_string(), n))
+            .collect(),
+    };
+    let verdict = |word: &str, verdict| ServerMessage::Verdict {
+        word: String::from(word),
+        verdict,
+    };
+    let error = |message: &str| ServerMessage::Error {
+        message: String::from(message),
+    };
+
+    let board = board_from_u64(7);
+    let dict = dict(&["ref", "remep", "pit", "toe"]);
+    let block = Dict::new();
+    let filter = filter::FilteredDict::new(&dict, &block);
+    let mut room = room::Room::new("lobby", &filter, Scoring::Classic);
+    let start = Instant::now();
+    let deadline = start + Duration::from_secs(180);
+    let start_message = |millis| ServerMessage::Start {
+        board,
+        millis,
+        scoring: Scoring::Classic,
+        min_len: 3,
+    };
+
+    let (ann_out, ann) = channel();
+    let (bob_out, bob) = channel();
+    assert!(room.join("ann", ann_out.clone(), start));
+    assert!(!room.join("ann", ann_out, start));
+    assert!(room.join("bob", bob_out, start));
+    assert_eq!(
+        messages(&ann),
+        vec![players(&["ann"]), players(&["ann", "bob"])]
+    );
+    assert_eq!(messages(&bob), vec![players(&["ann", "bob"])]);
+
+    room.word("ann", String::from("ref"), start);
+    assert_eq!(messages(&ann), vec![error("no round is running")]);
+    assert!(room.start(1, board, deadline, start));
+    assert!(!room.start(2, board, deadline, start));
+    assert_eq!(room.round_id(), Some(1));
+    assert_eq!(messages(&bob), vec![start_message(180_000)]);
+
+    room.word("ann", String::from("ref"), start);
+    room.word("bob", String::from("REF"), start);
+    room.word("bob", String::from("toe"), start);
+    room.word("bob", String::from("toe"), start);
+    assert_eq!(
+        messages(&bob),
+        vec![
+            progress(&[("ann", 1), ("bob", 0)]),
+            verdict("REF", round::Verdict::Correct),
+            progress(&[("ann", 1), ("bob", 1)]),
+            verdict("toe", round::Verdict::Correct),
+            progress(&[("ann", 1), ("bob", 2)]),
+            verdict("toe", round::Verdict::NotPresent),
+        ]
+    );
+
+    // leaving and coming back keeps the words found so far
+    room.leave("ann");
+    assert_eq!(messages(&bob), vec![players(&["bob"])]);
+    let (ann_out, ann) = channel();
+    assert!(room.join("ann", ann_out, start + Duration::from_secs(60)));
+    assert_eq!(
+        messages(&ann),
+        vec![
+            players(&["ann", "bob"]),
+            start_message(120_000),
+            progress(&[("ann", 1), ("bob", 2)]),
+        ]
+    );
+
+    // words arriving once time is up are turned away, even before the round is finished
+    room.word("ann", String::from("pit"), deadline);
+    assert_eq!(messages(&ann), vec![error("the round is over")]);
+
+    room.finish_round();
+    assert_eq!(room.round_id(), None);
+    let results = match messages(&bob).pop() {
+        Some(ServerMessage::Results { results }) => results,
+        message => panic!("unexpected message {:?}", message),
+    };
+    let words = |result: &round::RoundResult| (result.correct.clone(), result.cancelled.clone());
+    let found: BTreeMap<_, _> = results
+        .iter()
+        .map(|(name, r)| (name.as_str(), words(r)))
+        .collect();
+    assert_eq!(found["ann"], (vec![], vec![String::from("ref")]));
+    assert_eq!(
+        found["bob"],
+        (vec![String::from("toe")], vec![String::from("REF")])
+    );
+    assert!(results["ann"]
+        .missed
+        .contains(&"pit".parse::<BString>().unwrap()));
+    assert_eq!(results["bob"].score(), 1);
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn history_log() {
+    let board = board_from_u64(7);
+    let checker_dict = dict(&["ref", "remep", "pit", "toe"]);
+    let block = Dict::new();
+    let filter = filter::FilteredDict::new(&checker_dict, &block);
+    let mut checker = round::Checker::new(&board, &filter);
+    let words = ["ref", "pit", "ref"];
+    for word in words.iter() {
+        checker.check(word);
+    }
+    let result = checker.finish();
+    let game = history::GameRecord {
+        time: 1_700_000_000,
+        board,
+        seed: Some(7),
+        dice: Some(String::from("classic")),
+        dict: Some(history::dict_id(&checker_dict)),
+        scoring: Scoring::Classic,
+        min_len: 3,
+        players: vec![history::PlayerRecord {
+            name: String::from("ann"),
+            words: words
+                .iter()
+                .zip(1..)
+                .map(|(word, i)| history::WordRecord {
+                    word: String::from(*word),
+                    millis: i * 1500,
+                })
+                .collect(),
+            score: result.score(),
+            result,
+        }],
+    };
+    assert!(game.player("bob").is_none());
+    let ann = game.player("ann").unwrap();
+    assert_eq!(ann.words.len(), 3);
+    assert_eq!(ann.result.correct, vec!["ref", "pit"]);
+    assert_eq!(ann.result.not_present, vec!["ref"]);
+    assert_eq!(ann.score, 2);
+    assert_eq!(ann.result.max_score(), 5);
+
+    let path = std::env::temp_dir().join(format!("boggle-history-{}", std::process::id()));
+    assert!(io::read_history(&path).unwrap().is_empty());
+    io::append_history(&path, &game).unwrap();
+    io::append_history(&path, &game).unwrap();
+    let games = io::read_history(&path).unwrap();
+    assert_eq!(games, vec![game.clone(), game]);
+    let missed = &games[1].player("ann").unwrap().result.missed;
+    assert_eq!(
+        missed
+            .words()
+            .iter()
+            .map(|w| w.to_string())
+            .collect::<Vec<_>>(),
+        vec!["remep", "toe"]
+    );
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn fingerprint() {
+    assert_eq!(
+        dict(&["cat", "cot"]).fingerprint(),
+        dict(&["cot", "cat"]).fingerprint()
+    );
+    assert_ne!(
+        dict(&["cat", "cot"]).fingerprint(),
+        dict(&["cat", "cots"]).fingerprint()
+    );
+    assert_ne!(Dict::new().fingerprint(), dict(&["cat"]).fingerprint());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn player_stats() {
+    let board = board_from_u64(7);
+    let dict = dict(&["ref", "toe", "remep"]);
+    let block = Dict::new();
+    let filter = filter::FilteredDict::new(&dict, &block);
+    let game = |words: &[&str]| {
+        let mut checker = round::Checker::new(&board, &filter);
+        for word in words {
+            checker.check(word);
+        }
+        let result = checker.finish();
+        history::GameRecord {
+            time: 0,
+            board,
+            seed: None,
+            dice: None,
+            dict: None,
+            scoring: Scoring::Classic,
+            min_len: 3,
+            players: vec![history::PlayerRecord {
+                name: String::from("ann"),
+                words: Vec::new(),
+                score: result.score(),
+                result,
+            }],
+        }
+    };
+    let games = [game(&["ref"]), game(&["ref", "remep"])];
+    let stats = history::PlayerStats::new(&games, "ann");
+    assert_eq!(stats.rounds, 2);
+    assert_eq!(stats.average_score, 2.0);
+    assert_eq!(stats.points_found, 0.5);
+    assert_eq!(stats.longest_word.as_deref(), Some("remep"));
+    let missed: Vec<_> = stats
+        .missed
+        .iter()
+        .map(|m| (m.word.as_str(), m.times, m.points))
+        .collect();
+    assert_eq!(missed, vec![("toe", 2, 2), ("remep", 1, 2)]);
+    assert_eq!(stats.progress, vec![0.25, 0.75]);
+    assert_eq!(stats.improvement, 0.5);
+
+    // a word cancelled by another player was still found
+    let mut player = games[1].players[0].clone();
+    player.result.correct.retain(|word| word != "remep");
+    player.result.cancelled.push(String::from("remep"));
+    assert_eq!(player.longest_word(), Some("remep"));
+    assert_eq!(games[0].players[0].longest_word(), Some("ref"));
+
+    let stats = history::PlayerStats::new(&games, "bob");
+    assert_eq!(stats.rounds, 0);
+    assert!(stats.missed.is_empty());
+}
+
+#[test]
+fn trainer() {
+    let mut schedule = trainer::Schedule::default();
+    for word in ["quiet", "rhythm", "tsar"].iter() {
+        schedule.words.insert(
+            word.to_string(),
+            trainer::Card {
+                streak: 0,
+                due: 100,
+            },
+        );
+    }
+    schedule.words.get_mut("tsar").unwrap().due = 50;
+    assert!(schedule.due(49).is_empty());
+    assert_eq!(schedule.due(100), vec!["tsar", "quiet", "rhythm"]);
+    schedule.review("quiet", true, 100);
+    schedule.review("quiet", true, 200);
+    schedule.review("rhythm", false, 100);
+    assert_eq!(schedule.words["quiet"].streak, 2);
+    assert_eq!(schedule.words["quiet"].due, 200 + 3 * trainer::DAY);
+    assert_eq!(schedule.words["rhythm"].due, 100);
+    assert_eq!(schedule.next_due(), Some(50));
+
+    let dice = io::parse_dice_set(include_str!("../dice.txt"))
+        .unwrap()
+        .dice;
+    let mut rng = Pcg32::seed_from_u64(1);
+    let bstr = |s: &str| s.parse::<BString>().unwrap();
+    for word in ["quiet", "rhythm", "tsar", "abcdefghi"].iter() {
+        let board = trainer::plant(&bstr(word), &dice, &mut rng).unwrap();
+        assert!(board.find_path(&bstr(word)).is_some());
+    }
+    assert!(trainer::plant(&bstr("zzz"), &dice, &mut rng).is_none());
+    assert!(trainer::can_spell(&bstr("rhythm"), &dice));
+    assert!(!trainer::can_spell(&bstr("zzz"), &dice));
+    schedule.postpone("tsar", 50 + trainer::DAY);
+    assert_eq!(schedule.words["tsar"].streak, 0);
+    assert_eq!(schedule.due(100), vec!["rhythm"]);
+
+    let words = vec![bstr("zzz"), bstr("rhythm"), bstr("tsar")];
+    let (board, found) = trainer::practice_board(&words, &dice, 20, &mut rng).unwrap();
+    assert!(found.contains(&bstr("rhythm")));
+    assert_eq!(board.words_trie(&found).len(), found.len());
+    assert!(trainer::practice_board(&words[..1], &dice, 20, &mut rng).is_none());
+
+    // the second try plants the second word, which brings the third along with it
+    let words = vec![bstr("quiet"), bstr("tsars"), bstr("tsar")];
+    let (_, found) = trainer::practice_board(&words, &dice, 1, &mut rng).unwrap();
+    assert!(found.contains(&bstr("quiet")));
+    let (_, found) = trainer::practice_board(&words, &dice, 2, &mut rng).unwrap();
+    assert!(found.contains(&bstr("tsars")));
+    assert!(found.contains(&bstr("tsar")));
+}
@@ -0,0 +1,237 @@
+//! Practice for the words a player keeps missing.
+//!
+//! Each word being practised is reviewed on a schedule: a word found in practice is left for
+//! longer before it comes up again, and a word missed starts again from the beginning. Boards for
+//! practice are rolled with the words that are due laid out on them.
+
+#[cfg(feature = "serde")]
+use crate::history::{GameRecord, PlayerStats};
+use crate::*;
+use std::collections::BTreeMap;
+
+/// Days to wait before a word comes up again, by how many times in a row it has been found.
+pub const INTERVALS: [u64; 6] = [0, 1, 3, 7, 14, 30];
+
+/// The length of a day in seconds, the unit of [`INTERVALS`].
+pub const DAY: u64 = 86_400;
+
+/// The words being practised, and when each is next due.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Schedule {
+    pub words: BTreeMap<String, Card>,
+}
+
+/// How practice of one word is going.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Card {
+    /// How many times in a row the word has been found in practice.
+    pub streak: usize,
+    /// When the word is next due, in seconds since the Unix epoch.
+    pub due: u64,
+}
+
+impl Schedule {
+    /// Adds the words the player called `name` missed in at least `min_times` of `games`, due at
+    /// `now`. Words already in the schedule keep their place. Returns how many words were added.
+    #[cfg(feature = "serde")]
+    pub fn add_missed<'a>(
+        &mut self,
+        games: impl IntoIterator<Item = &'a GameRecord>,
+        name: &str,
+        min_times: u32,
+        now: u64,
+    ) -> usize {
+        let mut added = 0;
+        for missed in PlayerStats::new(games, name).missed {
+            if missed.times >= min_times && !self.words.contains_key(&missed.word) {
+                self.words.insert(
+                    missed.word,
+                    Card {
+                        streak: 0,
+                        due: now,
+                    },
+                );
+                added += 1;
+            }
+        }
+        added
+    }
+
+    /// The words due at `now`, those that have waited longest first.
+    pub fn due(&self, now: u64) -> Vec<&str> {
+        let mut due: Vec<(&str, u64)> = self
+            .words
+            .iter()
+            .filter(|(_, card)| card.due <= now)
+            .map(|(word, card)| (word.as_str(), card.due))
+            .collect();
+        // sorting is stable, so words due at the same time stay in alphabetical order
+        due.sort_by_key(|&(_, due)| due);
+        due.into_iter().map(|(word, _)| word).collect()
+    }
+
+    /// When the next word is due, if any are being practised.
+    pub fn next_due(&self) -> Option<u64> {
+        self.words.values().map(|card| card.due).min()
+    }
+
+    /// Puts a word off until `until` without counting it as reviewed, for a word that can't be
+    /// practised for now. Words not in the schedule are ignored.
+    pub fn postpone(&mut self, word: &str, until: u64) {
+        if let Some(card) = self.words.get_mut(word) {
+            card.due = until;
+        }
+    }
+
+    /// Records whether a word was found when it came up at `now`, and schedules it again. Words
+    /// not in the schedule are ignored.
+    pub fn review(&mut self, word: &str, found: bool, now: u64) {
+        if let Some(card) = self.words.get_mut(word) {
+            card.streak = if found { card.streak + 1 } else { 0 };
+            let days = INTERVALS[card.streak.min(INTERVALS.len() - 1)];
+            card.due = now + days * DAY;
+        }
+    }
+}
+
+/// Rolls a board with `word` laid along a random path, and the dice not needed for it rolled into
+/// the other places. Returns `None` if the dice can't spell the word.
+pub fn plant<R: Rng + ?Sized>(word: &BStr, dice: &Dice, rng: &mut R) -> Option<Board> {
+    if word.is_empty() || word.len() > 16 {
+        return None;
+    }
+    let mut order: Vec<usize> = (0..dice.len()).collect();
+    order.shuffle(rng);
+    let letters = match_dice(word, dice, &order)?;
+    let path = random_path(word.len(), rng)?;
+    let mut board = <[[BChar; 4]; 4]>::default();
+    let mut placed = [[false; 4]; 4];
+    for (&(r, c), (&die, &ch)) in path.iter().zip(letters.iter().zip(word.0.iter())) {
+        debug_assert!(dice[die].contains(&ch));
+        board[r][c] = ch;
+        placed[r][c] = true;
+    }
+    let mut rest: Vec<&[BChar; 6]> = (0..dice.len())
+        .filter(|die| !letters.contains(die))
+        .map(|die| &dice[die])
+        .collect();
+    rest.shuffle(rng);
+    let free = (0..16).filter(|&i| !placed[i / 4][i % 4]);
+    for (i, die) in free.zip(rest) {
+        board[i / 4][i % 4] = *die.choose(rng).unwrap();
+    }
+    Some(Board(board))
+}
+
+/// Whether `dice` can spell `word`, so that it can be [`plant`]ed on a board.
+pub fn can_spell(word: &BStr, dice: &Dice) -> bool {
+    let order: Vec<usize> = (0..dice.len()).collect();
+    !word.is_empty() && word.len() <= 16 && match_dice(word, dice, &order).is_some()
+}
+
+/// Chooses a different die to show each letter of `word`, as the index of the die for each
+/// letter, by finding a matching between letters and the dice showing them. Dice are tried in
+/// `order`.
+fn match_dice(word: &BStr, dice: &Dice, order: &[usize]) -> Option<Vec<usize>> {
+    // the letter each die has been given
+    let mut owners = [None; 16];
+    for i in 0..word.len() {
+        let mut seen = [false; 16];
+        if !give_die(i, word, dice, order, &mut owners, &mut seen) {
+            return None;
+        }
+    }
+    let mut letters = vec![0; word.len()];
+    for (die, owner) in owners.iter().enumerate() {
+        if let Some(i) = *owner {
+            letters[i] = die;
+        }
+    }
+    Some(letters)
+}
+
+/// Finds a die for letter `i`, moving other letters to other dice if need be.
+fn give_die(
+    i: usize,
+    word: &BStr,
+    dice: &Dice,
+    order: &[usize],
+    owners: &mut [Option<usize>; 16],
+    seen: &mut [bool; 16],
+) -> bool {
+    for &die in order.iter() {
+        if seen[die] || !dice[die].contains(&word.0[i]) {
+            continue;
+        }
+        seen[die] = true;
+        if owners[die].is_none_or(|j| give_die(j, word, dice, order, owners, seen)) {
+            owners[die] = Some(i);
+            return true;
+        }
+    }
+    false
+}
+
+/// Picks a random path of `len` distinct, adjacent tiles.
+fn random_path<R: Rng + ?Sized>(len: usize, rng: &mut R) -> Option<Vec<(usize, usize)>> {
+    let mut starts: Vec<(usize, usize)> = (0..16).map(|i| (i / 4, i % 4)).collect();
+    starts.shuffle(rng);
+    for start in starts {
+        let mut path = vec![start];
+        if extend_path(&mut path, len, rng) {
+            return Some(path);
+        }
+    }
+    None
+}
+
+fn extend_path<R: Rng + ?Sized>(path: &mut Vec<(usize, usize)>, len: usize, rng: &mut R) -> bool {
+    if path.len() == len {
+        return true;
+    }
+    let mut next: Vec<(usize, usize)> = neighbours(*path.last().unwrap())
+        .filter(|p| !path.contains(p))
+        .collect();
+    next.shuffle(rng);
+    for p in next {
+        path.push(p);
+        if extend_path(path, len, rng) {
+            return true;
+        }
+        path.pop();
+    }
+    false
+}
+
+/// Makes a board for practising `words`, which should be the most urgent first.
+///
+/// Each of `tries` boards has one of `words` planted on it, starting from a different word each
+/// time so that every word gets a turn, and the board with the most of `words` on it is chosen.
+/// Returns the board and the words found on it by the solver, or `None` if the dice can't spell
+/// any of the words.
+pub fn practice_board<R: Rng + ?Sized>(
+    words: &[BString],
+    dice: &Dice,
+    tries: usize,
+    rng: &mut R,
+) -> Option<(Board, Dict)> {
+    let targets: Dict = words.iter().map(|word| &**word).collect();
+    let mut best: Option<(Board, Dict)> = None;
+    for i in 0..tries.max(1) {
+        let first = i % words.len().max(1);
+        let board = words[first..]
+            .iter()
+            .chain(&words[..first])
+            .find_map(|word| plant(word, dice, rng))?;
+        let found = board.words_trie(&targets);
+        if best
+            .as_ref()
+            .is_none_or(|(_, most)| found.len() > most.len())
+        {
+            best = Some((board, found));
+        }
+    }
+    best
+}